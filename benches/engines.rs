@@ -0,0 +1,31 @@
+//! Runs the day 9 BOOST program in sensor boost mode with each engine.
+
+use aoc2019::input_files::read_content;
+use aoc2019::intcode::engine::Engine;
+use aoc2019::intcode::IntCodeComputer;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+
+fn boost(c: &mut Criterion) {
+    let program = IntCodeComputer::read_program(&read_content("data/day09.txt").unwrap());
+    let mut group = c.benchmark_group("day 9 BOOST");
+    for (name, engine) in [
+        ("interpreter", Engine::Interpreter),
+        ("cached", Engine::Cached),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut m = program.clone();
+                m.set_engine(engine);
+                m.add_input(2);
+                m.execute_until_halted().unwrap();
+                m.get_last_output()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, boost);
+criterion_main!(benches);
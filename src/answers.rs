@@ -0,0 +1,160 @@
+use crate::solution::Answer;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Expected answers by day and part, read from a small subset of TOML:
+///
+/// ```toml
+/// [day01]
+/// part1 = 3229279
+/// part2 = """
+/// #  #
+/// ####
+/// """
+/// ```
+///
+/// Numbers are numeric answers and strings are images.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Answers(BTreeMap<(usize, usize), Answer>);
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct AnswersError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AnswersError {}
+
+fn parse_number<T: std::str::FromStr>(text: &str, what: &str) -> Result<T, String> {
+    text.parse::<T>()
+        .map_err(|_| format!("invalid {} '{}'", what, text))
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Answers, AnswersError> {
+        let mut answers = Answers::default();
+        let mut day = None;
+        let mut lines = text.lines().enumerate();
+        while let Some((index, line)) = lines.next() {
+            let error = |message: String| AnswersError {
+                line: index + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(table) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+                let number = table
+                    .strip_prefix("day")
+                    .ok_or_else(|| error(format!("expected [dayNN], found [{}]", table)))?;
+                day = Some(parse_number::<usize>(number, "day").map_err(error)?);
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected 'partN = answer'".to_string()))?;
+            let key = key.trim();
+            let part = key
+                .strip_prefix("part")
+                .ok_or_else(|| error(format!("expected partN, found '{}'", key)))
+                .and_then(|x| parse_number::<usize>(x, "part").map_err(error))?;
+            let day = day.ok_or_else(|| error("answer before the first [dayNN]".to_string()))?;
+            let value = value.trim();
+            let answer = if value == "\"\"\"" {
+                let mut image = String::new();
+                loop {
+                    match lines.next() {
+                        Some((_, "\"\"\"")) => break,
+                        Some((_, row)) => {
+                            image.push_str(row);
+                            image.push('\n');
+                        }
+                        None => return Err(error("unterminated \"\"\" string".to_string())),
+                    }
+                }
+                Answer::Image(image)
+            } else if let Some(text) = value.strip_prefix('"').and_then(|x| x.strip_suffix('"')) {
+                Answer::Image(text.to_string())
+            } else {
+                Answer::Number(parse_number::<i64>(value, "answer").map_err(error)?)
+            };
+            if answers.0.insert((day, part), answer).is_some() {
+                return Err(error(format!("day {} part {} is given twice", day, part)));
+            }
+        }
+        Ok(answers)
+    }
+
+    pub fn get(&self, day: usize, part: usize) -> Option<&Answer> {
+        self.0.get(&(day, part))
+    }
+}
+
+/// Whether `actual` is the `expected` answer. Images are compared without
+/// trailing whitespace, which editors tend to strip.
+pub fn matches(expected: &Answer, actual: &Answer) -> bool {
+    match (expected, actual) {
+        (Answer::Image(expected), Answer::Image(actual)) => {
+            let rows = |image: &str| -> Vec<String> {
+                let image = image.trim_end_matches('\n');
+                image.lines().map(|x| x.trim_end().to_string()).collect()
+            };
+            rows(expected) == rows(actual)
+        }
+        _ => expected == actual,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::answers::matches;
+    use crate::answers::Answers;
+    use crate::answers::AnswersError;
+    use crate::input_files::read_content;
+    use crate::solution::Answer;
+    use crate::solutions;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            "# Comment\n[day01]\npart1 = 12\npart2 = \"\"\"\n# #\n ##  \n\"\"\"\n\n[day2]\npart1 = -3\n",
+        )
+        .unwrap();
+        assert_eq!(answers.get(1, 1), Some(&Answer::Number(12)));
+        assert_eq!(answers.get(2, 1), Some(&Answer::Number(-3)));
+        assert_eq!(answers.get(2, 2), None);
+        let image = answers.get(1, 2).unwrap();
+        assert!(matches(image, &Answer::Image("# #\n ##\n".to_string())));
+        assert!(!matches(image, &Answer::Image("# #\n##\n".to_string())));
+        assert_eq!(
+            Answers::parse("[day01]\npart1 = 1\npart1 = 2").unwrap_err(),
+            AnswersError {
+                line: 3,
+                message: "day 1 part 1 is given twice".to_string()
+            }
+        );
+        assert_eq!(
+            Answers::parse("[day01]\npart1 = x")
+                .unwrap_err()
+                .to_string(),
+            "line 2: invalid answer 'x'"
+        );
+    }
+
+    #[test]
+    fn test_every_day_has_answers() {
+        let answers = Answers::parse(&read_content("answers.toml").unwrap()).unwrap();
+        for solution in solutions() {
+            for part in 1..=2 {
+                assert!(answers.get(solution.day(), part).is_some());
+            }
+        }
+    }
+}
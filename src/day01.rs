@@ -1,4 +1,5 @@
-use crate::input_files::read_content;
+use crate::solution::Answer;
+use crate::solution::Solution;
 
 fn calculate_fuel_atomic(mass: i32) -> i32 {
     mass / 3 - 2
@@ -12,16 +13,37 @@ fn calculate_fuel(mass: i32) -> i32 {
     needed_fuel + calculate_fuel(needed_fuel)
 }
 
-pub fn execute() {
-    let mut atomic_fuel: i32 = 0;
-    let mut total_fuel: i32 = 0;
-    for line in read_content(&String::from("data/day01.txt")).lines() {
-        let mass = line.parse::<i32>().unwrap();
-        total_fuel += calculate_fuel(mass);
-        atomic_fuel += calculate_fuel_atomic(mass);
+fn total_fuel(masses: &[i32], calculate: fn(i32) -> i32) -> i32 {
+    masses.iter().map(|mass| calculate(*mass)).sum()
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Vec<i32>;
+
+    fn day(&self) -> usize {
+        1
+    }
+
+    fn title(&self) -> &'static str {
+        "The Tyranny of the Rocket Equation"
+    }
+
+    fn parse(&self, input: &str) -> Vec<i32> {
+        input
+            .lines()
+            .map(|line| line.parse::<i32>().unwrap())
+            .collect()
+    }
+
+    fn part1(&self, masses: &Vec<i32>) -> Answer {
+        total_fuel(masses, calculate_fuel_atomic).into()
+    }
+
+    fn part2(&self, masses: &Vec<i32>) -> Answer {
+        total_fuel(masses, calculate_fuel).into()
     }
-    println!("Part 1: Total fuel needed: {}", atomic_fuel);
-    println!("Part 2: Total fuel needed: {}", total_fuel);
 }
 
 #[cfg(test)]
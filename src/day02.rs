@@ -1,33 +1,56 @@
-use crate::input_files::read_content;
+use crate::intcode::patch::PatchSet;
+use crate::intcode::symbolic::solve;
+use crate::intcode::symbolic::SymbolicMachine;
 use crate::intcode::IntCodeComputer;
+use crate::solution::Answer;
+use crate::solution::Solution;
 
-fn part1() {
-    let content = read_content(&String::from("data/day02.txt"));
-    let mut m = IntCodeComputer::read_program(&content);
-    m.set_value(1, 12);
-    m.set_value(2, 2);
-    m.execute_until_stopped();
-    println!("Part 1: Value {}", m.get_value(0));
+fn part1(program: &IntCodeComputer) -> i64 {
+    let mut m = program.clone();
+    let patches = PatchSet::parse(include_str!("../data/day02.patch")).unwrap();
+    m.apply_patch(patches.get("1202 program alarm").unwrap())
+        .unwrap();
+    m.execute_until_halted().unwrap();
+    m.get_value(0)
 }
 
-fn part2() {
-    let content = read_content(&String::from("data/day02.txt"));
-    let c = content.matches(",").count() + 1;
-    for i in 1..c {
-        for j in 1..c {
-            let mut m = IntCodeComputer::read_program(&content);
-            m.set_value(1, i as i64);
-            m.set_value(2, j as i64);
-            m.execute_until_stopped();
-            if m.get_value(0) == 19690720 {
-                println!("Part 2: Noun {}, verb {}, output: {}", i, j, 100 * i + j);
-                return;
-            }
-        }
-    }
+/// Finds the noun and verb that make the program output 19690720.
+fn part2(program: &IntCodeComputer) -> i64 {
+    let mut m = SymbolicMachine::new(&program.get_memory());
+    m.make_symbolic(1, "noun");
+    m.make_symbolic(2, "verb");
+    m.run().unwrap();
+    let values = solve(
+        &m.get_value(0),
+        19690720,
+        &[("noun", 0..=99), ("verb", 0..=99)],
+    )
+    .expect("No noun and verb give the target");
+    100 * values["noun"] + values["verb"]
 }
 
-pub fn execute() {
-    part1();
-    part2();
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = IntCodeComputer;
+
+    fn day(&self) -> usize {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "1202 Program Alarm"
+    }
+
+    fn parse(&self, input: &str) -> IntCodeComputer {
+        IntCodeComputer::read_program(input)
+    }
+
+    fn part1(&self, program: &IntCodeComputer) -> Answer {
+        part1(program).into()
+    }
+
+    fn part2(&self, program: &IntCodeComputer) -> Answer {
+        part2(program).into()
+    }
 }
@@ -1,4 +1,6 @@
 use crate::position::Position;
+use crate::solution::Answer;
+use crate::solution::Solution;
 use std::cmp::min;
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -15,8 +17,8 @@ impl ManhattanDistance for Position {
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum Instruction {
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Instruction {
     LEFT(i32),
     RIGHT(i32),
     UP(i32),
@@ -86,9 +88,7 @@ fn parse_instructions(content: String) -> Vec<Instruction> {
     return instructions;
 }
 
-fn find_nearest_crossing(first_wire: String, second_wire: String) -> Option<i32> {
-    let instructions1 = parse_instructions(first_wire);
-    let instructions2 = parse_instructions(second_wire);
+fn find_nearest_crossing(first_wire: &[Instruction], second_wire: &[Instruction]) -> Option<i32> {
     let origin = Position { x: 0, y: 0 };
     let mut fms1 = FuelManagementSystem {
         current_position: origin,
@@ -102,11 +102,11 @@ fn find_nearest_crossing(first_wire: String, second_wire: String) -> Option<i32>
         known_wires: HashSet::new(),
         known_lengths: HashMap::new(),
     };
-    for instruction in instructions1 {
-        fms1.apply(instruction);
+    for instruction in first_wire {
+        fms1.apply(*instruction);
     }
-    for instruction in instructions2 {
-        fms2.apply(instruction);
+    for instruction in second_wire {
+        fms2.apply(*instruction);
     }
     let mut smallest_distance = None;
     for crossing in fms1.known_wires.intersection(&fms2.known_wires) {
@@ -118,9 +118,7 @@ fn find_nearest_crossing(first_wire: String, second_wire: String) -> Option<i32>
     return smallest_distance;
 }
 
-fn find_shortest_crossing(first_wire: String, second_wire: String) -> Option<i32> {
-    let instructions1 = parse_instructions(first_wire);
-    let instructions2 = parse_instructions(second_wire);
+fn find_shortest_crossing(first_wire: &[Instruction], second_wire: &[Instruction]) -> Option<i32> {
     let origin = Position { x: 0, y: 0 };
     let mut fms1 = FuelManagementSystem {
         current_position: origin,
@@ -134,11 +132,11 @@ fn find_shortest_crossing(first_wire: String, second_wire: String) -> Option<i32
         known_wires: HashSet::new(),
         known_lengths: HashMap::new(),
     };
-    for instruction in instructions1 {
-        fms1.apply(instruction);
+    for instruction in first_wire {
+        fms1.apply(*instruction);
     }
-    for instruction in instructions2 {
-        fms2.apply(instruction);
+    for instruction in second_wire {
+        fms2.apply(*instruction);
     }
     let mut smallest_distance = None;
     for crossing in fms1.known_wires.intersection(&fms2.known_wires) {
@@ -153,19 +151,41 @@ fn find_shortest_crossing(first_wire: String, second_wire: String) -> Option<i32
     return smallest_distance;
 }
 
-pub fn execute() {
-    let content = crate::input_files::read_content(&String::from("data/day03.txt"));
-    let lines: Vec<&str> = content.lines().collect();
-    let nearest_crossing = find_nearest_crossing(lines[0].to_string(), lines[1].to_string());
-    println!(
-        "Part 1: Nearest crossing at {}",
-        nearest_crossing.unwrap_or(-1)
-    );
-    let shortest_crossing = find_shortest_crossing(lines[0].to_string(), lines[1].to_string());
-    println!(
-        "Part 2: Shortest crossing at {}",
-        shortest_crossing.unwrap_or(-1)
-    );
+type Wires = (Vec<Instruction>, Vec<Instruction>);
+
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Wires;
+
+    fn day(&self) -> usize {
+        3
+    }
+
+    fn title(&self) -> &'static str {
+        "Crossed Wires"
+    }
+
+    fn parse(&self, input: &str) -> Wires {
+        let lines: Vec<&str> = input.lines().collect();
+        (
+            parse_instructions(lines[0].to_string()),
+            parse_instructions(lines[1].to_string()),
+        )
+    }
+
+    fn part1(&self, (first_wire, second_wire): &Wires) -> Answer {
+        find_nearest_crossing(first_wire, second_wire)
+            .expect("The wires never cross")
+            .into()
+    }
+
+    fn part2(&self, (first_wire, second_wire): &Wires) -> Answer {
+        find_shortest_crossing(first_wire, second_wire)
+            .expect("The wires never cross")
+            .into()
+    }
 }
 
 #[cfg(test)]
@@ -230,17 +250,17 @@ mod tests {
     #[test]
     fn test_nearest_crossing_execution() {
         let distance1 =
-            find_nearest_crossing("R8,U5,L5,D3".to_string(), "U7,R6,D4,L4".to_string()).unwrap();
+            find_nearest_crossing(&parse_instructions("R8,U5,L5,D3".to_string()), &parse_instructions("U7,R6,D4,L4".to_string())).unwrap();
         assert_eq!(distance1, 6);
         let distance2 = find_nearest_crossing(
-            "R75,D30,R83,U83,L12,D49,R71,U7,L72".to_string(),
-            "U62,R66,U55,R34,D71,R55,D58,R83".to_string(),
+            &parse_instructions("R75,D30,R83,U83,L12,D49,R71,U7,L72".to_string()),
+            &parse_instructions("U62,R66,U55,R34,D71,R55,D58,R83".to_string()),
         )
         .unwrap();
         assert_eq!(distance2, 159);
         let distance3 = find_nearest_crossing(
-            "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51".to_string(),
-            "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7".to_string(),
+            &parse_instructions("R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51".to_string()),
+            &parse_instructions("U98,R91,D20,R16,D67,R40,U7,R15,U6,R7".to_string()),
         )
         .unwrap();
         assert_eq!(distance3, 135);
@@ -249,17 +269,17 @@ mod tests {
     #[test]
     fn test_shortest_crossing_execution() {
         let distance1 =
-            find_shortest_crossing("R8,U5,L5,D3".to_string(), "U7,R6,D4,L4".to_string()).unwrap();
+            find_shortest_crossing(&parse_instructions("R8,U5,L5,D3".to_string()), &parse_instructions("U7,R6,D4,L4".to_string())).unwrap();
         assert_eq!(distance1, 30);
         let distance2 = find_shortest_crossing(
-            "R75,D30,R83,U83,L12,D49,R71,U7,L72".to_string(),
-            "U62,R66,U55,R34,D71,R55,D58,R83".to_string(),
+            &parse_instructions("R75,D30,R83,U83,L12,D49,R71,U7,L72".to_string()),
+            &parse_instructions("U62,R66,U55,R34,D71,R55,D58,R83".to_string()),
         )
         .unwrap();
         assert_eq!(distance2, 610);
         let distance3 = find_shortest_crossing(
-            "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51".to_string(),
-            "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7".to_string(),
+            &parse_instructions("R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51".to_string()),
+            &parse_instructions("U98,R91,D20,R16,D67,R40,U7,R15,U6,R7".to_string()),
         )
         .unwrap();
         assert_eq!(distance3, 410);
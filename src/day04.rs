@@ -1,3 +1,6 @@
+use crate::solution::Answer;
+use crate::solution::Solution;
+
 fn number_is_valid_part1(number: i32) -> bool {
     let num1 = number / 100000;
     let num2 = (number % 100000) / 10000;
@@ -63,11 +66,39 @@ fn count_valid_passwords_part2(start: i32, end: i32) -> i32 {
     return count;
 }
 
-pub fn execute() {
-    let count1 = count_valid_passwords_part1(108457, 562041);
-    println!("Part 1: Number of valid passwords in range: {}", count1);
-    let count2 = count_valid_passwords_part2(108457, 562041);
-    println!("Part 2: Number of valid passwords in range: {}", count2);
+/// Parses an inclusive range written as `start-end`.
+fn parse_range(content: &str) -> (i32, i32) {
+    let (start, end) = content
+        .trim()
+        .split_once('-')
+        .expect("The range should be written as start-end");
+    (start.parse().unwrap(), end.parse().unwrap())
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = (i32, i32);
+
+    fn day(&self) -> usize {
+        4
+    }
+
+    fn title(&self) -> &'static str {
+        "Secure Container"
+    }
+
+    fn parse(&self, input: &str) -> (i32, i32) {
+        parse_range(input)
+    }
+
+    fn part1(&self, &(start, end): &(i32, i32)) -> Answer {
+        count_valid_passwords_part1(start, end).into()
+    }
+
+    fn part2(&self, &(start, end): &(i32, i32)) -> Answer {
+        count_valid_passwords_part2(start, end).into()
+    }
 }
 
 #[cfg(test)]
@@ -79,6 +110,11 @@ mod tests {
         assert_eq!(crate::day04::number_is_valid_part1(123789), false);
     }
 
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(crate::day04::parse_range("108457-562041\n"), (108457, 562041));
+    }
+
     #[test]
     fn test_number_is_valid_part2() {
         assert_eq!(crate::day04::number_is_valid_part2(112233), true);
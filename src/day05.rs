@@ -1,18 +1,37 @@
-use crate::input_files::read_content;
 use crate::intcode::IntCodeComputer;
+use crate::solution::Answer;
+use crate::solution::Solution;
 
-pub fn execute() {
-    let content = read_content(&String::from("data/day05.txt"));
-    let mut m1 = IntCodeComputer::read_program_with_input(&content, 1);
-    m1.execute_until_stopped();
-    println!(
-        "Part 1: Diagnostic output: {}",
-        m1.get_last_output().unwrap()
-    );
-    let mut m2 = IntCodeComputer::read_program_with_input(&content, 5);
-    m2.execute_until_stopped();
-    println!(
-        "Part 2: Diagnostic output: {}",
-        m2.get_last_output().unwrap()
-    );
+/// Runs the diagnostic program for the system with the given ID.
+fn diagnostic_code(program: &IntCodeComputer, system: i64) -> i64 {
+    let mut m = program.clone();
+    m.add_input(system);
+    m.execute_until_halted().unwrap();
+    m.get_last_output().unwrap()
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = IntCodeComputer;
+
+    fn day(&self) -> usize {
+        5
+    }
+
+    fn title(&self) -> &'static str {
+        "Sunny with a Chance of Asteroids"
+    }
+
+    fn parse(&self, input: &str) -> IntCodeComputer {
+        IntCodeComputer::read_program(input)
+    }
+
+    fn part1(&self, program: &IntCodeComputer) -> Answer {
+        diagnostic_code(program, 1).into()
+    }
+
+    fn part2(&self, program: &IntCodeComputer) -> Answer {
+        diagnostic_code(program, 5).into()
+    }
 }
@@ -1,7 +1,9 @@
+use crate::solution::Answer;
+use crate::solution::Solution;
 use std::collections::HashMap;
 
 #[derive(Debug)]
-struct UniversalOrbitMap {
+pub struct UniversalOrbitMap {
     relationships: HashMap<String, String>,
 }
 
@@ -10,7 +12,7 @@ fn parse_line(line: String) -> (String, String) {
     return (parts[0].trim().to_string(), parts[1].trim().to_string());
 }
 
-fn parse_map(content: String) -> UniversalOrbitMap {
+fn parse_map(content: &str) -> UniversalOrbitMap {
     let mut map = UniversalOrbitMap {
         relationships: HashMap::new(),
     };
@@ -66,14 +68,31 @@ fn calculate_checksum(map: &UniversalOrbitMap) -> i32 {
     checksum
 }
 
-pub fn execute() {
-    let content = crate::input_files::read_content(&String::from("data/day06.txt"));
-    let map = parse_map(content);
-    println!("Part 1: Checksum {}", calculate_checksum(&map));
-    println!(
-        "Part 2: Shortest distance between YOU and SAN {}",
-        calculate_shortest_route(&map, "YOU".to_string(), "SAN".to_string())
-    );
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = UniversalOrbitMap;
+
+    fn day(&self) -> usize {
+        6
+    }
+
+    fn title(&self) -> &'static str {
+        "Universal Orbit Map"
+    }
+
+    fn parse(&self, input: &str) -> UniversalOrbitMap {
+        parse_map(input)
+    }
+
+    fn part1(&self, map: &UniversalOrbitMap) -> Answer {
+        calculate_checksum(map).into()
+    }
+
+    /// The shortest distance between YOU and SAN.
+    fn part2(&self, map: &UniversalOrbitMap) -> Answer {
+        calculate_shortest_route(map, "YOU".to_string(), "SAN".to_string()).into()
+    }
 }
 
 #[cfg(test)]
@@ -81,7 +100,7 @@ mod tests {
     #[test]
     fn test_calculate_checksum() {
         let map = crate::day06::parse_map(
-            "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\n".to_string(),
+            "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\n",
         );
         assert_eq!(crate::day06::calculate_checksum(&map), 42);
     }
@@ -89,7 +108,7 @@ mod tests {
     #[test]
     fn test_calculate_shortest_route() {
         let map = crate::day06::parse_map(
-            "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN".to_string(),
+            "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN",
         );
         assert_eq!(
             crate::day06::calculate_shortest_route(&map, "YOU".to_string(), "SAN".to_string()),
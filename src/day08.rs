@@ -1,3 +1,6 @@
+use crate::solution::Answer;
+use crate::solution::Solution;
+
 #[derive(Debug)]
 struct Layer {
     width: usize,
@@ -23,7 +26,7 @@ impl CharacterCounter for Layer {
 }
 
 #[derive(Debug)]
-struct Image {
+pub struct Image {
     width: usize,
     height: usize,
     layers: Vec<Layer>,
@@ -88,7 +91,7 @@ fn get_char(value: u8) -> char {
     }
 }
 
-fn draw_layer(layer: &Layer) {
+fn draw_layer(layer: &Layer) -> String {
     let mut output = String::new();
     for y in 0..layer.height {
         for x in 0..layer.width {
@@ -96,7 +99,7 @@ fn draw_layer(layer: &Layer) {
         }
         output.push('\n');
     }
-    println!("{}", output);
+    output
 }
 
 fn create_blank_image(width: usize, height: usize) -> Image {
@@ -121,19 +124,41 @@ fn part1(image: &Image) -> usize {
     best_value
 }
 
-fn part2(image: &Image) {
-    println!("Part 2:\n");
+fn part2(image: &Image) -> String {
     let layer = image.produce_layer();
-    draw_layer(&layer);
+    draw_layer(&layer)
 }
 
-pub fn execute() {
-    let content = crate::input_files::read_content(&String::from("data/day08.txt"));
+fn parse_image(content: &str) -> Image {
     let mut image = create_blank_image(25, 6);
     for c in content.trim().chars() {
         image.accept(c.to_digit(10).unwrap() as u8);
     }
-    let result_of_part_1 = part1(&image);
-    println!("Part 1: {}", result_of_part_1);
-    part2(&image);
+    image
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Image;
+
+    fn day(&self) -> usize {
+        8
+    }
+
+    fn title(&self) -> &'static str {
+        "Space Image Format"
+    }
+
+    fn parse(&self, input: &str) -> Image {
+        parse_image(input)
+    }
+
+    fn part1(&self, image: &Image) -> Answer {
+        part1(image).into()
+    }
+
+    fn part2(&self, image: &Image) -> Answer {
+        Answer::Image(part2(image))
+    }
 }
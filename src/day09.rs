@@ -1,12 +1,38 @@
-use crate::input_files::read_content;
 use crate::intcode::IntCodeComputer;
+use crate::solution::Answer;
+use crate::solution::Solution;
 
-pub fn execute() {
-    let content = read_content(&String::from("data/day09.txt"));
-    let mut m1 = IntCodeComputer::read_program_with_input(&content, 1);
-    m1.execute_until_stopped();
-    println!("Part 1: BOOST keycode: {}", m1.get_last_output().unwrap());
-    let mut m2 = IntCodeComputer::read_program_with_input(&content, 2);
-    m2.execute_until_stopped();
-    println!("Part 2: Distress signal: {}", m2.get_last_output().unwrap());
+fn boost(program: &IntCodeComputer, mode: i64) -> i64 {
+    let mut m = program.clone();
+    m.add_input(mode);
+    m.execute_until_halted().unwrap();
+    m.get_last_output().unwrap()
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = IntCodeComputer;
+
+    fn day(&self) -> usize {
+        9
+    }
+
+    fn title(&self) -> &'static str {
+        "Sensor Boost"
+    }
+
+    fn parse(&self, input: &str) -> IntCodeComputer {
+        IntCodeComputer::read_program(input)
+    }
+
+    /// The BOOST keycode, from running in test mode.
+    fn part1(&self, program: &IntCodeComputer) -> Answer {
+        boost(program, 1).into()
+    }
+
+    /// The coordinates of the distress signal.
+    fn part2(&self, program: &IntCodeComputer) -> Answer {
+        boost(program, 2).into()
+    }
 }
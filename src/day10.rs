@@ -1,5 +1,6 @@
-use crate::input_files::read_content;
 use crate::position::Position;
+use crate::solution::Answer;
+use crate::solution::Solution;
 use gcd::Gcd;
 use std::collections::HashSet;
 use std::f64::consts::FRAC_PI_2;
@@ -49,12 +50,12 @@ impl Quarter {
 }
 
 #[derive(Debug)]
-struct AsteroidField {
+pub struct AsteroidField {
     asteroids: HashSet<Position>,
 }
 
 impl AsteroidField {
-    fn parse(content: &String) -> AsteroidField {
+    fn parse(content: &str) -> AsteroidField {
         let mut asteroids = HashSet::new();
         let mut y = 0;
         for line in content.lines() {
@@ -202,18 +203,32 @@ fn find_200th_asteroid(asteroid_field: &AsteroidField, center: Position) -> Posi
     asteroids.get(199).unwrap().1
 }
 
-pub fn execute() {
-    let content = read_content(&String::from("data/day10.txt"));
-    let asteroid_field = AsteroidField::parse(&content);
-    let (count, best_asteroid) = find_best_asteroid(&asteroid_field);
-    println!("Part 1: {} at {:?}", count, best_asteroid);
-    let asteroid200 = find_200th_asteroid(&asteroid_field, best_asteroid);
-    let original_asteroid200 = asteroid200 + best_asteroid;
-    println!(
-        "Part 2: {:?} -> {}",
-        original_asteroid200,
-        original_asteroid200.x * 100 + original_asteroid200.y
-    );
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = AsteroidField;
+
+    fn day(&self) -> usize {
+        10
+    }
+
+    fn title(&self) -> &'static str {
+        "Monitoring Station"
+    }
+
+    fn parse(&self, input: &str) -> AsteroidField {
+        AsteroidField::parse(input)
+    }
+
+    fn part1(&self, asteroid_field: &AsteroidField) -> Answer {
+        find_best_asteroid(asteroid_field).0.into()
+    }
+
+    fn part2(&self, asteroid_field: &AsteroidField) -> Answer {
+        let (_, best_asteroid) = find_best_asteroid(asteroid_field);
+        let asteroid200 = find_200th_asteroid(asteroid_field, best_asteroid) + best_asteroid;
+        (asteroid200.x * 100 + asteroid200.y).into()
+    }
 }
 
 #[cfg(test)]
@@ -265,7 +280,7 @@ mod tests {
 
     #[test]
     fn evalute_known_pattern() {
-        let content = read_content(&String::from("data/day10_sample.txt"));
+        let content = read_content("data/day10_sample.txt").unwrap();
         let asteroid_field = AsteroidField::parse(&content);
         let (count, best_asteroid) = find_best_asteroid(&asteroid_field);
         assert_eq!(count, 210);
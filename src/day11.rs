@@ -1,6 +1,7 @@
-use crate::input_files::read_content;
 use crate::intcode::IntCodeComputer;
 use crate::position::Position;
+use crate::solution::Answer;
+use crate::solution::Solution;
 use std::collections::HashMap;
 
 enum Orientation {
@@ -114,19 +115,21 @@ impl HullSurface {
     }
 }
 
-fn part1(content: &String) {
-    let mut m = IntCodeComputer::read_program(&content);
+fn part1(program: &IntCodeComputer) -> usize {
+    let mut m = program.clone();
     let mut surface = HullSurface::new();
     let mut orientation = Orientation::new();
     let mut robot = Position::new(0, 0);
 
-    while !m.has_terminated() {
+    loop {
         let current_color = surface.get_color(&robot);
         m.add_input(current_color.to_number());
-        m.execute_until_stopped();
-        let output_size = m.get_output_size();
-        let new_color = Color::from_number(m.get_output(output_size - 2).unwrap());
-        let rotation_dir = m.get_output(output_size - 1).unwrap();
+        let output = m.run_until_output(2).unwrap();
+        if output.len() < 2 {
+            break;
+        }
+        let new_color = Color::from_number(output[0]);
+        let rotation_dir = output[1];
         orientation = match rotation_dir {
             0 => orientation.rotate_left(),
             1 => orientation.rotate_right(),
@@ -136,23 +139,25 @@ fn part1(content: &String) {
         robot = robot + orientation.get_move_offset();
     }
 
-    println!("Part 1: Number of tiles painted {}", surface.paint.len());
+    surface.paint.len()
 }
 
-fn part2(content: &String) {
-    let mut m = IntCodeComputer::read_program(&content);
+fn part2(program: &IntCodeComputer) -> String {
+    let mut m = program.clone();
     let mut surface = HullSurface::new();
     let mut orientation = Orientation::new();
     let mut robot = Position::new(0, 0);
     surface.set_color(&robot, Color::White);
 
-    while !m.has_terminated() {
+    loop {
         let current_color = surface.get_color(&robot);
         m.add_input(current_color.to_number());
-        m.execute_until_stopped();
-        let output_size = m.get_output_size();
-        let new_color = Color::from_number(m.get_output(output_size - 2).unwrap());
-        let rotation_dir = m.get_output(output_size - 1).unwrap();
+        let output = m.run_until_output(2).unwrap();
+        if output.len() < 2 {
+            break;
+        }
+        let new_color = Color::from_number(output[0]);
+        let rotation_dir = output[1];
         orientation = match rotation_dir {
             0 => orientation.rotate_left(),
             1 => orientation.rotate_right(),
@@ -162,11 +167,31 @@ fn part2(content: &String) {
         robot = robot + orientation.get_move_offset();
     }
 
-    println!("Part 2:\n{}", surface.render());
+    surface.render()
 }
 
-pub fn execute() {
-    let content = read_content(&String::from("data/day11.txt"));
-    part1(&content);
-    part2(&content);
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = IntCodeComputer;
+
+    fn day(&self) -> usize {
+        11
+    }
+
+    fn title(&self) -> &'static str {
+        "Space Police"
+    }
+
+    fn parse(&self, input: &str) -> IntCodeComputer {
+        IntCodeComputer::read_program(input)
+    }
+
+    fn part1(&self, program: &IntCodeComputer) -> Answer {
+        part1(program).into()
+    }
+
+    fn part2(&self, program: &IntCodeComputer) -> Answer {
+        Answer::Image(part2(program))
+    }
 }
@@ -1,11 +1,12 @@
-use crate::input_files::read_content;
 use crate::position3::Position3;
+use crate::solution::Answer;
+use crate::solution::Solution;
 use gcd::Gcd;
 use regex::Regex;
 use std::collections::HashSet;
 
 #[derive(Debug, Clone)]
-struct Moon {
+pub struct Moon {
     position: Position3,
     velocity: Position3,
 }
@@ -234,7 +235,7 @@ impl CompositeOrbits {
     }
 }
 
-fn parse_position(line: &String) -> Option<Position3> {
+fn parse_position(line: &str) -> Option<Position3> {
     let re = Regex::new(r"^<x=(-?\d+), y=(-?\d+), z=(-?\d+)>$").unwrap();
     let matched = re.captures(line)?;
     return Some(Position3::new(
@@ -244,10 +245,10 @@ fn parse_position(line: &String) -> Option<Position3> {
     ));
 }
 
-fn parse_orbits(content: &String) -> Vec<Moon> {
+fn parse_orbits(content: &str) -> Vec<Moon> {
     let mut moons = Vec::new();
     for line in content.lines() {
-        let position = parse_position(&line.to_string());
+        let position = parse_position(line);
         if position.is_some() {
             moons.push(Moon::new(position.unwrap()));
         }
@@ -256,8 +257,8 @@ fn parse_orbits(content: &String) -> Vec<Moon> {
     moons
 }
 
-fn part1(content: &String, print_steps: bool) {
-    let mut orbits = Orbits::new(parse_orbits(content));
+fn part1(moons: &[Moon], print_steps: bool) -> u64 {
+    let mut orbits = Orbits::new(moons.to_vec());
     for step in 0..1000 {
         if print_steps {
             println!("After {} steps:", step);
@@ -269,24 +270,38 @@ fn part1(content: &String, print_steps: bool) {
 
         orbits.simulate_step();
     }
-    println!(
-        "Part 1: Total energy after 1000 steps is {}",
-        orbits.total_energy()
-    );
+    orbits.total_energy()
 }
 
-fn part2(content: &String) {
-    let orbits = CompositeOrbits::new(parse_orbits(content));
-    println!(
-        "Part 2: The universe cycles in {:?} steps",
-        orbits.calculate_cycle()
-    );
+fn part2(moons: &[Moon]) -> u64 {
+    let orbits = CompositeOrbits::new(moons.to_vec());
+    orbits.calculate_cycle()
 }
 
-pub fn execute() {
-    let content = read_content(&"data/day12.txt".to_string());
-    part1(&content, false);
-    part2(&content);
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Vec<Moon>;
+
+    fn day(&self) -> usize {
+        12
+    }
+
+    fn title(&self) -> &'static str {
+        "The N-Body Problem"
+    }
+
+    fn parse(&self, input: &str) -> Vec<Moon> {
+        parse_orbits(input)
+    }
+
+    fn part1(&self, moons: &Vec<Moon>) -> Answer {
+        part1(moons, false).into()
+    }
+
+    fn part2(&self, moons: &Vec<Moon>) -> Answer {
+        part2(moons).into()
+    }
 }
 
 #[cfg(test)]
@@ -297,7 +312,7 @@ mod tests {
     #[test]
     fn test_parse_position() {
         assert_eq!(
-            parse_position(&("<x=-15, y=-14, z=12>".to_string())).unwrap(),
+            parse_position("<x=-15, y=-14, z=12>").unwrap(),
             Position3::new(-15, -14, 12)
         )
     }
@@ -1,6 +1,8 @@
-use crate::input_files::read_content;
+use crate::intcode::patch::PatchSet;
 use crate::intcode::IntCodeComputer;
 use crate::position::Position;
+use crate::solution::Answer;
+use crate::solution::Solution;
 use std::collections::HashMap;
 
 #[derive(PartialEq, Clone, Copy)]
@@ -110,20 +112,22 @@ struct ArcadeCabinet {
     display: Display,
     computer: IntCodeComputer,
     score: u64,
-    read_output: usize,
     tick: usize,
 }
 
 impl ArcadeCabinet {
-    fn new(program: &String) -> ArcadeCabinet {
+    fn new(program: &IntCodeComputer) -> ArcadeCabinet {
         let mut cabinet = ArcadeCabinet {
             display: Display::new(),
-            computer: IntCodeComputer::read_program(program),
+            computer: program.clone(),
             score: 0,
-            read_output: 0,
             tick: 0,
         };
-        cabinet.computer.set_value(0, 2);
+        let patches = PatchSet::parse(include_str!("../data/day13.patch")).unwrap();
+        cabinet
+            .computer
+            .apply_patch(patches.get("free play").unwrap())
+            .unwrap();
         cabinet.execute(0);
 
         cabinet
@@ -131,11 +135,12 @@ impl ArcadeCabinet {
 
     fn execute(&mut self, input: i64) {
         self.computer.add_input(input);
-        self.computer.execute_until_stopped();
-        for i in (self.read_output / 3)..(self.computer.get_output_size() / 3) {
-            let x = self.computer.get_output(i * 3).unwrap();
-            let y = self.computer.get_output(i * 3 + 1).unwrap();
-            let tile_id = self.computer.get_output(i * 3 + 2).unwrap();
+        loop {
+            let output = self.computer.run_until_output(3).unwrap();
+            if output.len() < 3 {
+                break;
+            }
+            let (x, y, tile_id) = (output[0], output[1], output[2]);
             if x == -1 && y == 0 {
                 self.score = tile_id as u64;
             } else {
@@ -145,7 +150,6 @@ impl ArcadeCabinet {
                 );
             }
         }
-        self.read_output = self.computer.get_output_size();
         self.tick += 1;
     }
 
@@ -188,23 +192,42 @@ impl ArcadeCabinet {
     }
 }
 
-fn part1(program: &String, render_game: bool) {
+fn part1(program: &IntCodeComputer, render_game: bool) -> usize {
     let mut arcade = ArcadeCabinet::new(program);
     arcade.execute(0);
-    println!("Part 1: Rendered {} blocks", arcade.get_block_count());
     if render_game {
         println!("{}", arcade.render());
     }
+    arcade.get_block_count()
 }
 
-fn part2(program: &String, render_game: bool) {
+fn part2(program: &IntCodeComputer, render_game: bool) -> u64 {
     let mut arcade = ArcadeCabinet::new(program);
-    let score = arcade.play(render_game);
-    println!("Part 2: Score: {}\n", score);
+    arcade.play(render_game)
 }
 
-pub fn execute() {
-    let content = read_content(&"data/day13.txt".to_string());
-    part1(&content, false);
-    part2(&content, false);
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = IntCodeComputer;
+
+    fn day(&self) -> usize {
+        13
+    }
+
+    fn title(&self) -> &'static str {
+        "Care Package"
+    }
+
+    fn parse(&self, input: &str) -> IntCodeComputer {
+        IntCodeComputer::read_program(input)
+    }
+
+    fn part1(&self, program: &IntCodeComputer) -> Answer {
+        part1(program, false).into()
+    }
+
+    fn part2(&self, program: &IntCodeComputer) -> Answer {
+        part2(program, false).into()
+    }
 }
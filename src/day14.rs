@@ -1,4 +1,5 @@
-use crate::input_files::read_content;
+use crate::solution::Answer;
+use crate::solution::Solution;
 use regex::Regex;
 use std::collections::HashMap;
 
@@ -10,13 +11,13 @@ enum Material {
 }
 
 impl Material {
-    fn parse(content: &String) -> Material {
+    fn parse(content: &str) -> Material {
         if content == "ORE" {
             Material::Ore
         } else if content == "FUEL" {
             Material::Fuel
         } else {
-            Material::Intermediate(content.clone())
+            Material::Intermediate(content.to_string())
         }
     }
 }
@@ -29,7 +30,7 @@ struct Reaction {
 }
 
 impl Reaction {
-    fn parse(content: &String) -> Option<Reaction> {
+    fn parse(content: &str) -> Option<Reaction> {
         let main_parts: Vec<&str> = content.trim().split(" => ").collect();
         assert!(main_parts.len() == 2, "Could not split '{}'", content);
         let mut inputs = HashMap::new();
@@ -37,12 +38,12 @@ impl Reaction {
         for input in main_parts.get(0)?.split(", ") {
             let matched = re.captures(input)?;
             let count = matched[1].parse::<u64>().ok()?;
-            let material = Material::parse(&matched[2].to_string());
+            let material = Material::parse(&matched[2]);
             inputs.insert(material, count);
         }
         let matched = re.captures(main_parts.get(1)?)?;
         let count = matched[1].parse::<u64>().ok()?;
-        let material = Material::parse(&matched[2].to_string());
+        let material = Material::parse(&matched[2]);
 
         Some(Reaction {
             inputs: inputs,
@@ -53,16 +54,16 @@ impl Reaction {
 }
 
 #[derive(Debug, Clone)]
-struct Nanofactory {
+pub struct Nanofactory {
     recipes: HashMap<Material, Reaction>,
     ranks: HashMap<Material, u64>,
 }
 
 impl Nanofactory {
-    fn new(content: &String) -> Option<Nanofactory> {
+    fn new(content: &str) -> Option<Nanofactory> {
         let mut recipes = HashMap::new();
         for line in content.lines() {
-            let reaction = Reaction::parse(&line.to_string())?;
+            let reaction = Reaction::parse(line)?;
             let output_material = reaction.output.clone();
             recipes.insert(output_material, reaction);
         }
@@ -167,26 +168,38 @@ impl Nanofactory {
     }
 }
 
-fn part1(recipe_description: &String) {
-    let factory = Nanofactory::new(recipe_description).unwrap();
-    println!(
-        "Part 1: {} ore needed",
-        factory.calculate_ore_needed_for_1_fuel()
-    );
+fn part1(factory: &Nanofactory) -> u64 {
+    factory.calculate_ore_needed_for_1_fuel()
 }
 
-fn part2(recipe_description: &String) {
-    let factory = Nanofactory::new(recipe_description).unwrap();
-    println!(
-        "Part 2: {} fuel produced with one trillion ore",
-        factory.calculate_maximum_fuel_for_1_trillion_ore()
-    );
+fn part2(factory: &Nanofactory) -> u64 {
+    factory.calculate_maximum_fuel_for_1_trillion_ore()
 }
 
-pub fn execute() {
-    let content = read_content(&"data/day14.txt".to_string());
-    part1(&content);
-    part2(&content);
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Nanofactory;
+
+    fn day(&self) -> usize {
+        14
+    }
+
+    fn title(&self) -> &'static str {
+        "Space Stoichiometry"
+    }
+
+    fn parse(&self, input: &str) -> Nanofactory {
+        Nanofactory::new(input).unwrap()
+    }
+
+    fn part1(&self, factory: &Nanofactory) -> Answer {
+        part1(factory).into()
+    }
+
+    fn part2(&self, factory: &Nanofactory) -> Answer {
+        part2(factory).into()
+    }
 }
 
 #[cfg(test)]
@@ -195,7 +208,7 @@ mod tests {
 
     fn factory1() -> Nanofactory {
         Nanofactory::new(
-            &"157 ORE => 5 NZVS
+            "157 ORE => 5 NZVS
         165 ORE => 6 DCFZ
         44 XJWVT, 5 KHKGT, 1 QDVJ, 29 NZVS, 9 GPVTF, 48 HKGWZ => 1 FUEL
         12 HKGWZ, 1 GPVTF, 8 PSHF => 9 QDVJ
@@ -203,15 +216,14 @@ mod tests {
         177 ORE => 5 HKGWZ
         7 DCFZ, 7 PSHF => 2 XJWVT
         165 ORE => 2 GPVTF
-        3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT"
-                .to_string(),
+        3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT",
         )
         .unwrap()
     }
 
     fn factory2() -> Nanofactory {
         Nanofactory::new(
-            &"2 VPVL, 7 FWMGM, 2 CXFTF, 11 MNCFX => 1 STKFG
+            "2 VPVL, 7 FWMGM, 2 CXFTF, 11 MNCFX => 1 STKFG
         17 NVRVD, 3 JNWZP => 8 VPVL
         53 STKFG, 6 MNCFX, 46 VJHF, 81 HVMC, 68 CXFTF, 25 GNMV => 1 FUEL
         22 VJHF, 37 MNCFX => 5 FWMGM
@@ -222,15 +234,14 @@ mod tests {
         145 ORE => 6 MNCFX
         1 NVRVD => 8 CXFTF
         1 VJHF, 6 MNCFX => 4 RFSQX
-        176 ORE => 6 VJHF"
-                .to_string(),
+        176 ORE => 6 VJHF",
         )
         .unwrap()
     }
 
     fn factory3() -> Nanofactory {
         Nanofactory::new(
-            &"171 ORE => 8 CNZTR
+            "171 ORE => 8 CNZTR
         7 ZLQW, 3 BMBT, 9 XCVML, 26 XMNCP, 1 WPTQ, 2 MZWV, 1 RJRHP => 4 PLWSL
         114 ORE => 4 BHXH
         14 VRPVC => 6 BMBT
@@ -246,8 +257,7 @@ mod tests {
         3 BHXH, 2 VRPVC => 7 MZWV
         121 ORE => 7 VRPVC
         7 XCVML => 6 RJRHP
-        5 BHXH, 4 VRPVC => 5 LTCX"
-                .to_string(),
+        5 BHXH, 4 VRPVC => 5 LTCX",
         )
         .unwrap()
     }
@@ -1,6 +1,7 @@
-use crate::input_files::read_content;
 use crate::intcode::IntCodeComputer;
 use crate::position::Position;
+use crate::solution::Answer;
+use crate::solution::Solution;
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 use std::collections::HashMap;
@@ -318,11 +319,11 @@ struct RobotEnvironment {
 }
 
 impl RobotEnvironment {
-    fn new(content: &String) -> RobotEnvironment {
+    fn new(program: &IntCodeComputer) -> RobotEnvironment {
         let mut environment = RobotEnvironment {
             robot_position: Position::new(0, 0),
             map: RobotMap::new(),
-            computer: IntCodeComputer::read_program(content),
+            computer: program.clone(),
         };
         environment.map.put(environment.robot_position, Tile::Start);
 
@@ -332,8 +333,7 @@ impl RobotEnvironment {
     fn attempt_movement(&mut self, direction: &Direction) -> bool {
         let new_position = self.robot_position + direction.as_position();
         self.computer.add_input(direction.as_machine_api());
-        self.computer.execute_until_stopped();
-        let response_code = self.computer.get_last_output();
+        let response_code = self.computer.run_until_output(1).unwrap().first().copied();
         match response_code {
             Some(0) => {
                 // Hit a wall, could not move
@@ -395,30 +395,44 @@ impl RobotEnvironment {
     }
 }
 
-fn part1(content: &String) {
-    let mut robot_environment = RobotEnvironment::new(content);
+fn part1(program: &IntCodeComputer) -> usize {
+    let mut robot_environment = RobotEnvironment::new(program);
     robot_environment.explore();
-    println!(
-        "Part 1: Shortest route to oxygen system is {}",
-        robot_environment
-            .get_shorted_path_length_to_oxygen()
-            .unwrap_or(usize::MAX)
-    );
+    robot_environment
+        .get_shorted_path_length_to_oxygen()
+        .expect("No route to the oxygen system")
 }
 
-fn part2(content: &String) {
-    let mut robot_environment = RobotEnvironment::new(content);
+fn part2(program: &IntCodeComputer) -> usize {
+    let mut robot_environment = RobotEnvironment::new(program);
     robot_environment.explore();
-    println!(
-        "Part 2: Time taken to fill with oxygen is {} minutes",
-        robot_environment
-            .get_oxygen_fill_time()
-            .unwrap_or(usize::MAX)
-    );
+    robot_environment
+        .get_oxygen_fill_time()
+        .expect("No oxygen system to fill from")
 }
 
-pub fn execute() {
-    let content = read_content(&"data/day15.txt".to_string());
-    part1(&content);
-    part2(&content);
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = IntCodeComputer;
+
+    fn day(&self) -> usize {
+        15
+    }
+
+    fn title(&self) -> &'static str {
+        "Oxygen System"
+    }
+
+    fn parse(&self, input: &str) -> IntCodeComputer {
+        IntCodeComputer::read_program(input)
+    }
+
+    fn part1(&self, program: &IntCodeComputer) -> Answer {
+        part1(program).into()
+    }
+
+    fn part2(&self, program: &IntCodeComputer) -> Answer {
+        part2(program).into()
+    }
 }
@@ -0,0 +1,119 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fmt;
+use std::io::Read;
+use std::io::Write;
+
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// The most that `decompress` will produce. Programs and snapshots are far
+/// smaller, and the limit stops a small crafted file from using up memory.
+const MAX_DECOMPRESSED: usize = 64 << 20;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum GzipError {
+    NotGzip,
+    TooLarge(usize),
+    InvalidData(String),
+}
+
+impl fmt::Display for GzipError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GzipError::NotGzip => write!(f, "not gzip data"),
+            GzipError::TooLarge(limit) => {
+                write!(f, "decompressed data is larger than {} bytes", limit)
+            }
+            GzipError::InvalidData(reason) => write!(f, "invalid compressed data: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for GzipError {}
+
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    // Writing to a Vec cannot fail
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn decompress_limited(data: &[u8], limit: usize) -> Result<Vec<u8>, GzipError> {
+    if !is_gzip(data) {
+        return Err(GzipError::NotGzip);
+    }
+    let mut output = Vec::new();
+    GzDecoder::new(data)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut output)
+        .map_err(|error| GzipError::InvalidData(error.to_string()))?;
+    if output.len() > limit {
+        return Err(GzipError::TooLarge(limit));
+    }
+    Ok(output)
+}
+
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, GzipError> {
+    decompress_limited(data, MAX_DECOMPRESSED)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gzip::compress;
+    use crate::gzip::decompress;
+    use crate::gzip::decompress_limited;
+    use crate::gzip::GzipError;
+
+    #[test]
+    fn test_round_trip() {
+        let text = std::fs::read("data/day09.txt").unwrap();
+        let compressed = compress(&text);
+        assert!(compressed.len() < text.len() / 2, "{}", compressed.len());
+        assert_eq!(decompress(&compressed).unwrap(), text);
+        assert_eq!(decompress(&compress(b"")).unwrap(), b"");
+    }
+
+    #[test]
+    fn test_reads_gzip_tool_output() {
+        let compressed = std::fs::read("data/day09.txt.gz").unwrap();
+        let text = std::fs::read("data/day09.txt").unwrap();
+        assert_eq!(decompress(&compressed).unwrap(), text);
+    }
+
+    #[test]
+    fn test_corrupt_and_truncated() {
+        let compressed = compress(b"1,2,3,4,5");
+        let mut corrupt = compressed.clone();
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 1;
+        assert!(matches!(
+            decompress(&corrupt),
+            Err(GzipError::InvalidData(_))
+        ));
+        for length in [3, 12, compressed.len() - 1] {
+            assert!(
+                matches!(
+                    decompress(&compressed[..length]),
+                    Err(GzipError::InvalidData(_))
+                ),
+                "{}",
+                length
+            );
+        }
+        assert_eq!(decompress(b"1,2,3"), Err(GzipError::NotGzip));
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let compressed = compress(&[b'0'; 1000]);
+        assert_eq!(decompress_limited(&compressed, 1000).unwrap().len(), 1000);
+        assert_eq!(
+            decompress_limited(&compressed, 999),
+            Err(GzipError::TooLarge(999))
+        );
+    }
+}
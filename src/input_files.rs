@@ -1,3 +1,56 @@
-pub fn read_content(filename: &String) -> String {
-    std::fs::read_to_string(filename).expect("Should have been able to read file")
+use std::fmt;
+use std::io::Read;
+
+/// A file that could not be read, and why.
+#[derive(Debug)]
+pub struct InputError {
+    pub path: String,
+    pub error: std::io::Error,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.path.as_str() {
+            "-" => write!(f, "Could not read standard input: {}", self.error),
+            path => write!(f, "Could not read {}: {}", path, self.error),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Reads a whole file, or standard input if the name is `-`.
+pub fn read_bytes(filename: &str) -> Result<Vec<u8>, InputError> {
+    let result = match filename {
+        "-" => {
+            let mut data = Vec::new();
+            std::io::stdin().read_to_end(&mut data).map(|_| data)
+        }
+        path => std::fs::read(path),
+    };
+    result.map_err(|error| InputError {
+        path: filename.to_string(),
+        error,
+    })
+}
+
+/// Reads a whole text file, or standard input if the name is `-`.
+pub fn read_content(filename: &str) -> Result<String, InputError> {
+    String::from_utf8(read_bytes(filename)?).map_err(|error| InputError {
+        path: filename.to_string(),
+        error: std::io::Error::new(std::io::ErrorKind::InvalidData, error),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input_files::read_content;
+
+    #[test]
+    fn test_missing_file() {
+        let error = read_content("data/day99.txt").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Could not read data/day99.txt: "));
+    }
 }
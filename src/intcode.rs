@@ -1,245 +1,915 @@
+use crate::intcode::channels::IntcodeInput;
+use crate::intcode::channels::IntcodeOutput;
+use crate::intcode::engine::DecodeCache;
+use crate::intcode::engine::Engine;
+use crate::intcode::extension::Extensions;
+use crate::intcode::history::History;
+use crate::intcode::memory::Memory;
+use crate::intcode::memory::OutOfMemory;
+use crate::intcode::memory::PagedMemory;
+use crate::intcode::trace::TraceEvent;
+use crate::intcode::trace::Tracer;
+use crate::intcode::trace::TracerHook;
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+use std::time::Instant;
+
+pub mod analysis;
+pub mod ascii;
+pub mod assembler;
+pub mod channels;
+pub mod coverage;
+pub mod debugger;
+pub mod disassembler;
+pub mod engine;
+pub mod extension;
+pub mod fuzz;
+pub mod history;
+pub mod image;
+pub mod memory;
+pub mod network;
+pub mod patch;
+pub mod snapshot;
+pub mod symbolic;
+pub mod trace;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum IntcodeError {
+    InvalidOpcode {
+        ip: usize,
+        instruction: i64,
+    },
+    InvalidMode {
+        ip: usize,
+        instruction: i64,
+        mode: i64,
+    },
+    WriteToImmediate {
+        ip: usize,
+        instruction: i64,
+    },
+    NegativeAddress {
+        ip: usize,
+        instruction: i64,
+        address: i64,
+    },
+    InputExhausted {
+        ip: usize,
+        instruction: i64,
+    },
+    Overflow {
+        ip: usize,
+        instruction: i64,
+    },
+    MemoryLimit {
+        ip: usize,
+        instruction: i64,
+        address: usize,
+    },
+    /// Raised by an extension instruction.
+    Trap {
+        ip: usize,
+        instruction: i64,
+    },
+    /// The tracer could not record the instruction at `ip`.
+    TraceFailed {
+        ip: usize,
+        message: String,
+    },
+    /// What the machine printed before stopping at `ip` could not be written.
+    OutputFailed {
+        ip: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntcodeError::InvalidOpcode { ip, instruction } => {
+                write!(f, "invalid opcode in {} at {}", instruction, ip)
+            }
+            IntcodeError::InvalidMode {
+                ip,
+                instruction,
+                mode,
+            } => write!(f, "invalid mode {} in {} at {}", mode, instruction, ip),
+            IntcodeError::WriteToImmediate { ip, instruction } => write!(
+                f,
+                "write to immediate parameter in {} at {}",
+                instruction, ip
+            ),
+            IntcodeError::NegativeAddress {
+                ip,
+                instruction,
+                address,
+            } => write!(
+                f,
+                "negative address {} used by {} at {}",
+                address, instruction, ip
+            ),
+            IntcodeError::InputExhausted { ip, instruction } => {
+                write!(f, "input exhausted by {} at {}", instruction, ip)
+            }
+            IntcodeError::Overflow { ip, instruction } => {
+                write!(f, "arithmetic overflow in {} at {}", instruction, ip)
+            }
+            IntcodeError::MemoryLimit {
+                ip,
+                instruction,
+                address,
+            } => write!(
+                f,
+                "memory limit exceeded writing {} by {} at {}",
+                address, instruction, ip
+            ),
+            IntcodeError::Trap { ip, instruction } => {
+                write!(f, "trap {} at {}", instruction, ip)
+            }
+            IntcodeError::TraceFailed { ip, message } => {
+                write!(f, "could not write trace at {}: {}", ip, message)
+            }
+            IntcodeError::OutputFailed { ip, message } => {
+                write!(f, "could not write output at {}: {}", ip, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IntcodeError {}
+
 #[derive(Debug, PartialEq, Clone)]
 enum State {
     Running,
     Waiting,
+    /// Stopped by an extension instruction. Running again continues after it.
+    Yielded,
     Halted,
+    Faulted(IntcodeError),
+}
+
+/// How far a budgeted run got.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Outcome {
+    Halted,
+    Waiting,
+    Yielded,
+    /// The budget ran out while the machine was still running. Running it
+    /// again continues where it stopped.
+    BudgetExhausted,
+}
+
+/// Limits on how long a run may take.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Budget {
+    cycles: Option<u64>,
+    deadline: Option<Instant>,
+}
+
+/// The clock is only read once per this many instructions.
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+
+impl Budget {
+    pub fn cycles(cycles: u64) -> Budget {
+        Budget {
+            cycles: Some(cycles),
+            deadline: None,
+        }
+    }
+
+    pub fn with_deadline(self, deadline: Instant) -> Budget {
+        Budget {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Budget {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    fn is_exhausted(&self, executed: u64) -> bool {
+        if self.cycles.is_some_and(|x| executed >= x) {
+            return true;
+        }
+        match self.deadline {
+            Some(deadline) if executed.is_multiple_of(DEADLINE_CHECK_INTERVAL) => {
+                Instant::now() >= deadline
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct IntCodeComputer {
     current_op: usize,
-    memory: Vec<i64>,
+    memory: PagedMemory,
     state: State,
-    panicked: bool,
-    current_input: usize,
-    input: Vec<i64>,
-    output: Vec<i64>,
+    inputs_consumed: usize,
+    input: VecDeque<i64>,
+    output: VecDeque<i64>,
+    last_output: Option<i64>,
     relative_base: i64,
+    cache: Option<DecodeCache>,
+    tracer: TracerHook,
+    history: Option<History>,
+    extensions: Extensions,
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Parameter {
     Position(i64),
     Immediate(i64),
     Relative(i64),
 }
 
-pub enum Op {
-    Add(Parameter, Parameter, Parameter),
-    Multiply(Parameter, Parameter, Parameter),
-    Input(Parameter),
-    Output(Parameter),
-    JumpIfTrue(Parameter, Parameter),
-    JumpIfFalse(Parameter, Parameter),
-    LessThan(Parameter, Parameter, Parameter),
-    Equals(Parameter, Parameter, Parameter),
-    AdjustRelativeBase(Parameter),
+/// An instruction. Its parameters are as written in memory unless the
+/// engine has resolved them into another form.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Op<P = Parameter> {
+    Add(P, P, P),
+    Multiply(P, P, P),
+    Input(P),
+    Output(P),
+    JumpIfTrue(P, P),
+    JumpIfFalse(P, P),
+    LessThan(P, P, P),
+    Equals(P, P, P),
+    AdjustRelativeBase(P),
     Halt,
 }
 
+/// How the executor reads and writes through a parameter, whichever form it is in.
+trait Access: Copy {
+    fn read(self, m: &IntCodeComputer) -> Result<i64, IntcodeError>;
+    fn address(self, m: &IntCodeComputer) -> Result<usize, IntcodeError>;
+
+    fn write(self, m: &mut IntCodeComputer, value: i64) -> Result<(), IntcodeError> {
+        let address = self.address(m)?;
+        m.store(address, value)
+    }
+}
+
+impl Access for Parameter {
+    fn read(self, m: &IntCodeComputer) -> Result<i64, IntcodeError> {
+        m.read_parameter(&self)
+    }
+
+    fn address(self, m: &IntCodeComputer) -> Result<usize, IntcodeError> {
+        m.resolve_address(&self)
+    }
+
+    fn write(self, m: &mut IntCodeComputer, value: i64) -> Result<(), IntcodeError> {
+        m.write_parameter(&self, value)
+    }
+}
+
 impl Parameter {
-    fn from(mode: i64, value: i64) -> Parameter {
+    fn from(mode: i64, value: i64) -> Option<Parameter> {
         match mode {
-            0 => Parameter::Position(value),
-            1 => Parameter::Immediate(value),
-            2 => Parameter::Relative(value),
-            _ => panic!("Unsupported mode {}", mode),
+            0 => Some(Parameter::Position(value)),
+            1 => Some(Parameter::Immediate(value)),
+            2 => Some(Parameter::Relative(value)),
+            _ => None,
+        }
+    }
+
+    pub fn mode(&self) -> i64 {
+        match self {
+            Parameter::Position(_) => 0,
+            Parameter::Immediate(_) => 1,
+            Parameter::Relative(_) => 2,
+        }
+    }
+
+    pub fn value(&self) -> i64 {
+        match self {
+            Parameter::Position(x) | Parameter::Immediate(x) | Parameter::Relative(x) => *x,
         }
     }
 }
 
+impl<P> Op<P> {
+    /// Converts every parameter with `f`, keeping the instruction.
+    pub fn map<Q>(self, f: impl Fn(P) -> Q) -> Op<Q> {
+        match self {
+            Op::Add(a, b, c) => Op::Add(f(a), f(b), f(c)),
+            Op::Multiply(a, b, c) => Op::Multiply(f(a), f(b), f(c)),
+            Op::Input(a) => Op::Input(f(a)),
+            Op::Output(a) => Op::Output(f(a)),
+            Op::JumpIfTrue(a, b) => Op::JumpIfTrue(f(a), f(b)),
+            Op::JumpIfFalse(a, b) => Op::JumpIfFalse(f(a), f(b)),
+            Op::LessThan(a, b, c) => Op::LessThan(f(a), f(b), f(c)),
+            Op::Equals(a, b, c) => Op::Equals(f(a), f(b), f(c)),
+            Op::AdjustRelativeBase(a) => Op::AdjustRelativeBase(f(a)),
+            Op::Halt => Op::Halt,
+        }
+    }
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Parameter::Position(x) => write!(f, "[{}]", x),
+            Parameter::Immediate(x) => write!(f, "#{}", x),
+            Parameter::Relative(x) if *x < 0 => write!(f, "rb{}", x),
+            Parameter::Relative(x) => write!(f, "rb+{}", x),
+        }
+    }
+}
+
+impl Op {
+    /// Decodes the instruction at `ip`, treating memory past the end as zeroes.
+    pub fn decode<M: Memory + ?Sized>(memory: &M, ip: usize) -> Result<Op, IntcodeError> {
+        let instruction = memory.read(ip);
+        let parameter = |index: usize| {
+            let mode = (instruction / 10_i64.pow(index as u32 + 1)) % 10;
+            Parameter::from(mode, memory.read(ip + index)).ok_or(IntcodeError::InvalidMode {
+                ip,
+                instruction,
+                mode,
+            })
+        };
+
+        Ok(match instruction % 100 {
+            1 => Op::Add(parameter(1)?, parameter(2)?, parameter(3)?),
+            2 => Op::Multiply(parameter(1)?, parameter(2)?, parameter(3)?),
+            3 => Op::Input(parameter(1)?),
+            4 => Op::Output(parameter(1)?),
+            5 => Op::JumpIfTrue(parameter(1)?, parameter(2)?),
+            6 => Op::JumpIfFalse(parameter(1)?, parameter(2)?),
+            7 => Op::LessThan(parameter(1)?, parameter(2)?, parameter(3)?),
+            8 => Op::Equals(parameter(1)?, parameter(2)?, parameter(3)?),
+            9 => Op::AdjustRelativeBase(parameter(1)?),
+            99 => Op::Halt,
+            _ => return Err(IntcodeError::InvalidOpcode { ip, instruction }),
+        })
+    }
+
+    pub fn opcode(&self) -> i64 {
+        match self {
+            Op::Add(..) => 1,
+            Op::Multiply(..) => 2,
+            Op::Input(..) => 3,
+            Op::Output(..) => 4,
+            Op::JumpIfTrue(..) => 5,
+            Op::JumpIfFalse(..) => 6,
+            Op::LessThan(..) => 7,
+            Op::Equals(..) => 8,
+            Op::AdjustRelativeBase(..) => 9,
+            Op::Halt => 99,
+        }
+    }
+
+    /// Encodes the instruction back into memory words, without any redundant mode digits.
+    pub fn encode(&self) -> Vec<i64> {
+        let mut words = vec![self.opcode()];
+        let mut mode_factor = 100;
+        for parameter in self.parameters() {
+            words[0] += parameter.mode() * mode_factor;
+            words.push(parameter.value());
+            mode_factor *= 10;
+        }
+        words
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Op::Add(..) => "ADD",
+            Op::Multiply(..) => "MUL",
+            Op::Input(..) => "IN",
+            Op::Output(..) => "OUT",
+            Op::JumpIfTrue(..) => "JNZ",
+            Op::JumpIfFalse(..) => "JZ",
+            Op::LessThan(..) => "LT",
+            Op::Equals(..) => "EQ",
+            Op::AdjustRelativeBase(..) => "ARB",
+            Op::Halt => "HLT",
+        }
+    }
+
+    pub fn parameters(&self) -> Vec<&Parameter> {
+        match self {
+            Op::Add(a, b, c)
+            | Op::Multiply(a, b, c)
+            | Op::LessThan(a, b, c)
+            | Op::Equals(a, b, c) => {
+                vec![a, b, c]
+            }
+            Op::JumpIfTrue(a, b) | Op::JumpIfFalse(a, b) => vec![a, b],
+            Op::Input(a) | Op::Output(a) | Op::AdjustRelativeBase(a) => vec![a],
+            Op::Halt => vec![],
+        }
+    }
+
+    /// The parameter the instruction writes its result to, if any.
+    pub fn destination(&self) -> Option<&Parameter> {
+        match self {
+            Op::Add(_, _, o)
+            | Op::Multiply(_, _, o)
+            | Op::LessThan(_, _, o)
+            | Op::Equals(_, _, o) => Some(o),
+            Op::Input(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// Number of memory words taken by the instruction, including the opcode.
+    pub fn size(&self) -> usize {
+        self.parameters().len() + 1
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        for (i, parameter) in self.parameters().iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            write!(f, "{}{}", separator, parameter)?;
+        }
+        Ok(())
+    }
+}
+
 impl IntCodeComputer {
-    pub fn read_program(content: &String) -> IntCodeComputer {
-        let c = content.matches(",").count() + 1;
-        let mut m = IntCodeComputer {
+    /// Reads a comma separated program, panicking with the position of any
+    /// bad word. `image::load` reads the other formats.
+    pub fn read_program(content: &str) -> IntCodeComputer {
+        let words = image::parse_text(content).unwrap_or_else(|error| panic!("{}", error));
+        IntCodeComputer::from_words(&words)
+    }
+
+    fn from_words(words: &[i64]) -> IntCodeComputer {
+        IntCodeComputer {
             current_op: 0,
-            memory: vec![0; c],
+            memory: PagedMemory::from_words(words),
             state: State::Running,
-            panicked: false,
-            current_input: 0,
-            input: Vec::new(),
-            output: Vec::new(),
+            inputs_consumed: 0,
+            input: VecDeque::new(),
+            output: VecDeque::new(),
+            last_output: None,
             relative_base: 0,
-        };
-        let mut i = 0;
-        for line in content.split(",") {
-            let value = line.trim().parse::<i64>().unwrap();
-            m.memory[i] = value;
-            i += 1;
+            cache: None,
+            tracer: TracerHook::default(),
+            history: None,
+            extensions: Extensions::default(),
         }
-        return m;
     }
 
-    pub fn read_program_with_input(content: &String, value: i64) -> IntCodeComputer {
+    #[cfg(test)]
+    pub fn read_program_with_input(content: &str, value: i64) -> IntCodeComputer {
         let mut m = IntCodeComputer::read_program(content);
         m.add_input(value);
         m
     }
 
-    fn interpret_op(&self) -> Op {
-        let instruction = self.memory[self.current_op];
-        let opcode = instruction % 100;
-        let mode1 = (instruction / 100) % 10;
-        let mode2 = (instruction / 1000) % 10;
-        let mode3 = (instruction / 10000) % 10;
+    fn current_instruction(&self) -> i64 {
+        self.get_value(self.current_op)
+    }
 
-        match opcode {
-            1 => Op::Add(
-                Parameter::from(mode1, self.memory[self.current_op + 1]),
-                Parameter::from(mode2, self.memory[self.current_op + 2]),
-                Parameter::from(mode3, self.memory[self.current_op + 3]),
-            ),
-            2 => Op::Multiply(
-                Parameter::from(mode1, self.memory[self.current_op + 1]),
-                Parameter::from(mode2, self.memory[self.current_op + 2]),
-                Parameter::from(mode3, self.memory[self.current_op + 3]),
-            ),
-            3 => Op::Input(Parameter::from(mode1, self.memory[self.current_op + 1])),
-            4 => Op::Output(Parameter::from(mode1, self.memory[self.current_op + 1])),
-            5 => Op::JumpIfTrue(
-                Parameter::from(mode1, self.memory[self.current_op + 1]),
-                Parameter::from(mode2, self.memory[self.current_op + 2]),
-            ),
-            6 => Op::JumpIfFalse(
-                Parameter::from(mode1, self.memory[self.current_op + 1]),
-                Parameter::from(mode2, self.memory[self.current_op + 2]),
-            ),
-            7 => Op::LessThan(
-                Parameter::from(mode1, self.memory[self.current_op + 1]),
-                Parameter::from(mode2, self.memory[self.current_op + 2]),
-                Parameter::from(mode3, self.memory[self.current_op + 3]),
-            ),
-            8 => Op::Equals(
-                Parameter::from(mode1, self.memory[self.current_op + 1]),
-                Parameter::from(mode2, self.memory[self.current_op + 2]),
-                Parameter::from(mode3, self.memory[self.current_op + 3]),
-            ),
-            9 => Op::AdjustRelativeBase(Parameter::from(mode1, self.memory[self.current_op + 1])),
-            99 => Op::Halt,
-            _ => panic!("Invalid opcode {}", opcode),
+    fn interpret_op(&self) -> Result<Op, IntcodeError> {
+        Op::decode(&self.memory, self.current_op)
+    }
+
+    pub fn set_engine(&mut self, engine: Engine) {
+        self.cache = match engine {
+            Engine::Interpreter => None,
+            Engine::Cached => Some(DecodeCache::default()),
+        };
+    }
+
+    fn write_memory(&mut self, index: usize, value: i64) -> Result<(), OutOfMemory> {
+        self.memory.write(index, value)?;
+        if let Some(cache) = &mut self.cache {
+            cache.invalidate(index);
         }
+        Ok(())
     }
 
-    pub fn set_value(&mut self, index: usize, value: i64) {
-        while index >= self.memory.len() {
-            self.memory.push(0);
+    /// Calls `tracer` for every instruction executed from now on.
+    pub fn set_tracer(&mut self, tracer: Box<dyn Tracer>) {
+        self.tracer = TracerHook(Some(tracer));
+    }
+
+    /// Flushes the tracer once a run is over. A trace that could not be
+    /// written faults the machine, as it would have during the run.
+    pub fn finish_trace(&mut self) -> Result<(), IntcodeError> {
+        let tracer = match self.tracer.0.as_mut() {
+            Some(tracer) => tracer,
+            None => return Ok(()),
+        };
+        tracer.flush();
+        match (tracer.error(), &self.state) {
+            (_, State::Faulted(error)) => Err(error.clone()),
+            (Some(message), _) => {
+                let error = IntcodeError::TraceFailed {
+                    ip: self.current_op,
+                    message,
+                };
+                self.state = State::Faulted(error.clone());
+                Err(error)
+            }
+            (None, _) => Ok(()),
         }
-        self.memory[index] = value;
+    }
+
+    pub fn take_tracer(&mut self) -> Option<Box<dyn Tracer>> {
+        self.tracer.0.take()
+    }
+
+    /// Panics if the write goes over the memory limit.
+    pub fn set_value(&mut self, index: usize, value: i64) {
+        self.write_memory(index, value)
+            .expect("Memory limit exceeded");
+    }
+
+    /// Limits how many words of memory the machine may allocate. Going over
+    /// the limit faults the machine with `IntcodeError::MemoryLimit`.
+    pub fn set_memory_limit(&mut self, words: Option<usize>) {
+        self.memory.set_limit(words);
+    }
+
+    /// The memory from address 0 up to the highest address written, not
+    /// including far addresses.
+    pub fn get_memory(&self) -> Vec<i64> {
+        self.memory.dense()
     }
 
     pub fn get_value(&self, index: usize) -> i64 {
-        if index >= self.memory.len() {
-            return 0;
+        self.memory.read(index)
+    }
+
+    fn negative_address(&self, address: i64) -> IntcodeError {
+        IntcodeError::NegativeAddress {
+            ip: self.current_op,
+            instruction: self.current_instruction(),
+            address,
         }
-        self.memory[index]
     }
 
-    fn read_parameter(&self, parameter: &Parameter) -> i64 {
+    fn overflow(&self) -> IntcodeError {
+        IntcodeError::Overflow {
+            ip: self.current_op,
+            instruction: self.current_instruction(),
+        }
+    }
+
+    fn to_address(&self, address: i64) -> Result<usize, IntcodeError> {
+        usize::try_from(address).map_err(|_| self.negative_address(address))
+    }
+
+    fn resolve_address(&self, parameter: &Parameter) -> Result<usize, IntcodeError> {
         match parameter {
-            Parameter::Immediate(x) => *x,
-            Parameter::Position(x) => self.get_value(*x as usize),
-            Parameter::Relative(x) => self.get_value((*x + self.relative_base) as usize),
+            Parameter::Immediate(_) => Err(IntcodeError::WriteToImmediate {
+                ip: self.current_op,
+                instruction: self.current_instruction(),
+            }),
+            Parameter::Position(x) => self.to_address(*x),
+            Parameter::Relative(x) => {
+                let address = x
+                    .checked_add(self.relative_base)
+                    .ok_or_else(|| self.overflow())?;
+                self.to_address(address)
+            }
         }
     }
 
-    fn write_parameter(&mut self, parameter: &Parameter, value: i64) {
+    /// The memory address the next instruction will write to, if it writes at all.
+    fn write_target(&self) -> Option<usize> {
+        let op = self.interpret_op().ok()?;
+        self.resolve_address(op.destination()?).ok()
+    }
+
+    pub fn read_parameter(&self, parameter: &Parameter) -> Result<i64, IntcodeError> {
         match parameter {
-            Parameter::Immediate(_) => panic!("Cannot write to an immediate parameter!"),
-            Parameter::Position(x) => self.set_value(*x as usize, value),
-            Parameter::Relative(x) => self.set_value((*x + self.relative_base) as usize, value),
+            Parameter::Immediate(x) => Ok(*x),
+            _ => Ok(self.get_value(self.resolve_address(parameter)?)),
+        }
+    }
+
+    pub fn write_parameter(
+        &mut self,
+        parameter: &Parameter,
+        value: i64,
+    ) -> Result<(), IntcodeError> {
+        let address = self.resolve_address(parameter)?;
+        self.store(address, value)
+    }
+
+    fn store(&mut self, address: usize, value: i64) -> Result<(), IntcodeError> {
+        self.write_memory(address, value)
+            .map_err(|_| IntcodeError::MemoryLimit {
+                ip: self.current_op,
+                instruction: self.current_instruction(),
+                address,
+            })
+    }
+
+    fn jump(&mut self, target: i64) -> Result<(), IntcodeError> {
+        self.current_op = self.to_address(target)?;
+        Ok(())
+    }
+
+    /// Runs `f` with the machine's own input and output queues as its channels.
+    fn with_own_channels<T>(
+        &mut self,
+        f: impl FnOnce(&mut IntCodeComputer, &mut VecDeque<i64>, &mut VecDeque<i64>) -> T,
+    ) -> T {
+        let mut input = std::mem::take(&mut self.input);
+        let mut output = std::mem::take(&mut self.output);
+        let result = f(self, &mut input, &mut output);
+        self.input = input;
+        self.output = output;
+        result
+    }
+
+    fn execute_step(&mut self) -> Result<(), IntcodeError> {
+        self.with_own_channels(|m, input, output| m.execute_step_with(input, output))
+    }
+
+    fn execute_step_with(
+        &mut self,
+        input: &mut dyn IntcodeInput,
+        output: &mut dyn IntcodeOutput,
+    ) -> Result<(), IntcodeError> {
+        match &self.state {
+            State::Running => {}
+            State::Faulted(error) => return Err(error.clone()),
+            _ => return Ok(()),
+        }
+        let traced = match self.tracer.0 {
+            Some(_) => self.begin_trace(),
+            None => None,
+        };
+        let undo = match self.history {
+            Some(_) => Some((self.begin_undo(), self.inputs_consumed)),
+            None => None,
+        };
+        let result = self.execute_op(input, output);
+        if let Err(error) = &result {
+            self.state = State::Faulted(error.clone());
+        }
+        if let Some((entry, inputs_consumed)) = undo {
+            self.finish_undo(entry, inputs_consumed);
+        }
+        if let (Some(mut event), Some(tracer)) = (traced, self.tracer.0.as_mut()) {
+            if result.is_ok() && self.state != State::Waiting {
+                event.write = event
+                    .write
+                    .map(|(address, _)| (address, self.memory.read(address)));
+                tracer.trace(&event);
+                if let Some(message) = tracer.error() {
+                    let error = IntcodeError::TraceFailed {
+                        ip: event.ip,
+                        message,
+                    };
+                    self.state = State::Faulted(error.clone());
+                    return Err(error);
+                }
+            }
+        }
+        result
+    }
+
+    /// Describes the instruction about to be executed. The value written is
+    /// filled in once it has executed.
+    fn begin_trace(&self) -> Option<TraceEvent> {
+        let op = self.interpret_op().ok()?;
+        let destination = op.destination();
+        // The destination is always the last parameter
+        let read = op.parameters().len() - destination.iter().count();
+        let mut operands = Vec::new();
+        for parameter in op.parameters().into_iter().take(read) {
+            operands.push(self.read_parameter(parameter).ok()?);
+        }
+        let write = match destination {
+            Some(parameter) => Some((self.resolve_address(parameter).ok()?, 0)),
+            None => None,
         };
+        Some(TraceEvent {
+            ip: self.current_op,
+            op,
+            operands,
+            write,
+            relative_base: self.relative_base,
+        })
     }
 
-    fn execute_step(&mut self) {
-        if self.state != State::Running {
-            return;
+    fn execute_op(
+        &mut self,
+        input: &mut dyn IntcodeInput,
+        output: &mut dyn IntcodeOutput,
+    ) -> Result<(), IntcodeError> {
+        let cached = self
+            .cache
+            .as_mut()
+            .map(|cache| cache.decode(&self.memory, self.current_op));
+        let error = match cached {
+            Some(Ok(op)) => return self.execute(op, input, output),
+            Some(Err(error)) => error,
+            None => match self.interpret_op() {
+                Ok(op) => return self.execute(op, input, output),
+                Err(error) => error,
+            },
+        };
+        match error {
+            IntcodeError::InvalidOpcode { instruction, .. }
+                if self.extensions.handles(instruction) =>
+            {
+                self.execute_extension()
+            }
+            error => Err(error),
         }
-        match self.interpret_op() {
+    }
+
+    /// Executes `op`, decoded by the selected engine.
+    fn execute<P: Access>(
+        &mut self,
+        op: Op<P>,
+        input: &mut dyn IntcodeInput,
+        output: &mut dyn IntcodeOutput,
+    ) -> Result<(), IntcodeError> {
+        match op {
             Op::Add(l, r, o) => {
-                self.write_parameter(&o, self.read_parameter(&l) + self.read_parameter(&r));
+                let value = l
+                    .read(self)?
+                    .checked_add(r.read(self)?)
+                    .ok_or_else(|| self.overflow())?;
+                o.write(self, value)?;
                 self.current_op += 4;
             }
             Op::Multiply(l, r, o) => {
-                self.write_parameter(&o, self.read_parameter(&l) * self.read_parameter(&r));
+                let value = l
+                    .read(self)?
+                    .checked_mul(r.read(self)?)
+                    .ok_or_else(|| self.overflow())?;
+                o.write(self, value)?;
                 self.current_op += 4;
             }
             Op::Input(o) => {
-                if self.current_input < self.input.len() {
-                    self.write_parameter(&o, self.input[self.current_input]);
+                // Resolve the destination first so that a bad parameter does not consume input
+                let address = o.address(self)?;
+                if let Some(value) = input.next_input() {
+                    self.store(address, value)?;
                     self.current_op += 2;
-                    self.current_input += 1;
+                    self.inputs_consumed += 1;
                 } else {
                     self.state = State::Waiting;
                 }
             }
             Op::Output(o) => {
-                self.output.push(self.read_parameter(&o));
+                let value = o.read(self)?;
+                output.write_output(value);
+                self.last_output = Some(value);
                 self.current_op += 2;
             }
             Op::JumpIfTrue(t, new_op) => {
-                if self.read_parameter(&t) != 0 {
-                    self.current_op = self.read_parameter(&new_op) as usize;
+                if t.read(self)? != 0 {
+                    self.jump(new_op.read(self)?)?;
                 } else {
                     self.current_op += 3;
                 }
             }
             Op::JumpIfFalse(t, new_op) => {
-                if self.read_parameter(&t) == 0 {
-                    self.current_op = self.read_parameter(&new_op) as usize;
+                if t.read(self)? == 0 {
+                    self.jump(new_op.read(self)?)?;
                 } else {
                     self.current_op += 3;
                 }
             }
             Op::LessThan(l, r, o) => {
-                if self.read_parameter(&l) < self.read_parameter(&r) {
-                    self.write_parameter(&o, 1);
+                if l.read(self)? < r.read(self)? {
+                    o.write(self, 1)?;
                 } else {
-                    self.write_parameter(&o, 0);
+                    o.write(self, 0)?;
                 }
                 self.current_op += 4;
             }
             Op::Equals(l, r, o) => {
-                if self.read_parameter(&l) == self.read_parameter(&r) {
-                    self.write_parameter(&o, 1);
+                if l.read(self)? == r.read(self)? {
+                    o.write(self, 1)?;
                 } else {
-                    self.write_parameter(&o, 0);
+                    o.write(self, 0)?;
                 }
                 self.current_op += 4;
             }
             Op::AdjustRelativeBase(o) => {
-                let offset = self.read_parameter(&o);
-                self.relative_base += offset;
+                let offset = o.read(self)?;
+                self.relative_base = self
+                    .relative_base
+                    .checked_add(offset)
+                    .ok_or_else(|| self.overflow())?;
                 self.current_op += 2;
             }
             Op::Halt => {
                 self.state = State::Halted;
             }
         };
+        Ok(())
+    }
+
+    /// Lets a machine that is waiting for input or has yielded run again.
+    fn resume(&mut self) {
+        if matches!(self.state, State::Waiting | State::Yielded) {
+            self.state = State::Running;
+        }
+    }
+
+    pub fn execute_until_stopped(&mut self) -> Result<(), IntcodeError> {
+        self.with_own_channels(|m, input, output| m.execute_with(input, output))
     }
 
-    pub fn execute_until_stopped(&mut self) {
+    /// Runs until the machine halts or `input` has nothing more to give.
+    pub fn execute_with(
+        &mut self,
+        input: &mut dyn IntcodeInput,
+        output: &mut dyn IntcodeOutput,
+    ) -> Result<(), IntcodeError> {
+        self.resume();
         while self.state == State::Running {
-            self.execute_step();
+            self.execute_step_with(input, output)?;
+        }
+        match &self.state {
+            State::Faulted(error) => Err(error.clone()),
+            _ => Ok(()),
         }
     }
 
-    pub fn get_output(&self, index: usize) -> Option<i64> {
-        if index < self.output.len() {
-            Some(self.output[index])
-        } else {
-            None
+    /// Like `execute_with`, but stops with `Outcome::BudgetExhausted` once the
+    /// budget runs out.
+    pub fn execute_with_budget(
+        &mut self,
+        input: &mut dyn IntcodeInput,
+        output: &mut dyn IntcodeOutput,
+        budget: Budget,
+    ) -> Result<Outcome, IntcodeError> {
+        self.resume();
+        let mut executed = 0;
+        while self.state == State::Running {
+            if budget.is_exhausted(executed) {
+                return Ok(Outcome::BudgetExhausted);
+            }
+            self.execute_step_with(input, output)?;
+            executed += 1;
+        }
+        match &self.state {
+            State::Faulted(error) => Err(error.clone()),
+            State::Waiting => Ok(Outcome::Waiting),
+            State::Yielded => Ok(Outcome::Yielded),
+            _ => Ok(Outcome::Halted),
         }
     }
 
-    pub fn get_last_output(&self) -> Option<i64> {
-        self.get_output(self.output.len() - 1)
+    pub fn execute_until_stopped_within(
+        &mut self,
+        budget: Budget,
+    ) -> Result<Outcome, IntcodeError> {
+        self.with_own_channels(|m, input, output| m.execute_with_budget(input, output, budget))
     }
 
-    pub fn get_output_size(&self) -> usize {
-        self.output.len()
+    /// Runs until `count` new values have been output, or the machine stops, and
+    /// returns them. Fewer than `count` values are returned if the machine halted
+    /// or is waiting for input.
+    pub fn run_until_output(&mut self, count: usize) -> Result<Vec<i64>, IntcodeError> {
+        self.resume();
+        while self.output.len() < count && self.state == State::Running {
+            self.execute_step()?;
+        }
+        if let State::Faulted(error) = &self.state {
+            return Err(error.clone());
+        }
+        let available = count.min(self.output.len());
+        Ok(self.output.drain(..available).collect())
+    }
+
+    /// Removes and returns all output that has not been read yet.
+    pub fn take_output(&mut self) -> Vec<i64> {
+        self.output.drain(..).collect()
+    }
+
+    /// Runs the program to completion, treating a wait for more input as an error.
+    pub fn execute_until_halted(&mut self) -> Result<(), IntcodeError> {
+        self.execute_until_stopped()?;
+        while self.state == State::Yielded {
+            self.execute_until_stopped()?;
+        }
+        if self.state == State::Waiting {
+            return Err(IntcodeError::InputExhausted {
+                ip: self.current_op,
+                instruction: self.current_instruction(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn get_last_output(&self) -> Option<i64> {
+        self.last_output
     }
 
     pub fn add_input(&mut self, value: i64) {
-        self.input.push(value);
+        self.input.push_back(value);
         if self.state == State::Waiting {
             self.state = State::Running;
         }
@@ -252,12 +922,16 @@ impl IntCodeComputer {
 
 #[cfg(test)]
 mod tests {
+    use crate::intcode::Budget;
     use crate::intcode::IntCodeComputer;
+    use crate::intcode::IntcodeError;
+    use crate::intcode::Outcome;
+    use std::time::Duration;
 
     #[test]
     fn test_simple_program() {
         let mut m = IntCodeComputer::read_program(&String::from("1,9,10,3,2,3,11,0,99,30,40,50"));
-        m.execute_until_stopped();
+        m.execute_until_stopped().unwrap();
         let mut expected =
             IntCodeComputer::read_program(&String::from("3500,9,10,70,2,3,11,0,99,30,40,50"));
         expected.current_op = 8;
@@ -268,65 +942,56 @@ mod tests {
     #[test]
     fn test_simple_io_program() {
         let mut m = IntCodeComputer::read_program_with_input(&String::from("3,0,4,0,99"), 77);
-        m.execute_until_stopped();
-        assert_eq!(m.output.len(), 1);
-        assert_eq!(m.output[0], 77);
+        m.execute_until_stopped().unwrap();
+        assert_eq!(m.take_output(), vec![77]);
     }
 
     #[test]
     fn test_eq_positional() {
         let mut m1 =
             IntCodeComputer::read_program_with_input(&String::from("3,9,8,9,10,9,4,9,99,-1,8"), 8);
-        m1.execute_until_stopped();
-        assert_eq!(m1.output.len(), 1);
-        assert_eq!(m1.output[0], 1);
+        m1.execute_until_stopped().unwrap();
+        assert_eq!(m1.take_output(), vec![1]);
         let mut m2 =
             IntCodeComputer::read_program_with_input(&String::from("3,9,8,9,10,9,4,9,99,-1,8"), 9);
-        m2.execute_until_stopped();
-        assert_eq!(m2.output.len(), 1);
-        assert_eq!(m2.output[0], 0);
+        m2.execute_until_stopped().unwrap();
+        assert_eq!(m2.take_output(), vec![0]);
     }
 
     #[test]
     fn test_lt_positional() {
         let mut m1 =
             IntCodeComputer::read_program_with_input(&String::from("3,9,7,9,10,9,4,9,99,-1,8"), 7);
-        m1.execute_until_stopped();
-        assert_eq!(m1.output.len(), 1);
-        assert_eq!(m1.output[0], 1);
+        m1.execute_until_stopped().unwrap();
+        assert_eq!(m1.take_output(), vec![1]);
         let mut m2 =
             IntCodeComputer::read_program_with_input(&String::from("3,9,7,9,10,9,4,9,99,-1,8"), 9);
-        m2.execute_until_stopped();
-        assert_eq!(m2.output.len(), 1);
-        assert_eq!(m2.output[0], 0);
+        m2.execute_until_stopped().unwrap();
+        assert_eq!(m2.take_output(), vec![0]);
     }
 
     #[test]
     fn test_eq_immediate() {
         let mut m1 =
             IntCodeComputer::read_program_with_input(&String::from("3,3,1108,-1,8,3,4,3,99"), 8);
-        m1.execute_until_stopped();
-        assert_eq!(m1.output.len(), 1);
-        assert_eq!(m1.output[0], 1);
+        m1.execute_until_stopped().unwrap();
+        assert_eq!(m1.take_output(), vec![1]);
         let mut m2 =
             IntCodeComputer::read_program_with_input(&String::from("3,3,1108,-1,8,3,4,3,99"), 9);
-        m2.execute_until_stopped();
-        assert_eq!(m2.output.len(), 1);
-        assert_eq!(m2.output[0], 0);
+        m2.execute_until_stopped().unwrap();
+        assert_eq!(m2.take_output(), vec![0]);
     }
 
     #[test]
     fn test_lt_immediate() {
         let mut m1 =
             IntCodeComputer::read_program_with_input(&String::from("3,3,1107,-1,8,3,4,3,99"), 7);
-        m1.execute_until_stopped();
-        assert_eq!(m1.output.len(), 1);
-        assert_eq!(m1.output[0], 1);
+        m1.execute_until_stopped().unwrap();
+        assert_eq!(m1.take_output(), vec![1]);
         let mut m2 =
             IntCodeComputer::read_program_with_input(&String::from("3,3,1107,-1,8,3,4,3,99"), 9);
-        m2.execute_until_stopped();
-        assert_eq!(m2.output.len(), 1);
-        assert_eq!(m2.output[0], 0);
+        m2.execute_until_stopped().unwrap();
+        assert_eq!(m2.take_output(), vec![0]);
     }
 
     #[test]
@@ -335,16 +1000,14 @@ mod tests {
             &String::from("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9"),
             7,
         );
-        m1.execute_until_stopped();
-        assert_eq!(m1.output.len(), 1);
-        assert_eq!(m1.output[0], 1);
+        m1.execute_until_stopped().unwrap();
+        assert_eq!(m1.take_output(), vec![1]);
         let mut m2 = IntCodeComputer::read_program_with_input(
             &String::from("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9"),
             0,
         );
-        m2.execute_until_stopped();
-        assert_eq!(m2.output.len(), 1);
-        assert_eq!(m2.output[0], 0);
+        m2.execute_until_stopped().unwrap();
+        assert_eq!(m2.take_output(), vec![0]);
     }
 
     #[test]
@@ -353,32 +1016,27 @@ mod tests {
             &String::from("3,3,1105,-1,9,1101,0,0,12,4,12,99,1"),
             7,
         );
-        m1.execute_until_stopped();
-        assert_eq!(m1.output.len(), 1);
-        assert_eq!(m1.output[0], 1);
+        m1.execute_until_stopped().unwrap();
+        assert_eq!(m1.take_output(), vec![1]);
         let mut m2 = IntCodeComputer::read_program_with_input(
             &String::from("3,3,1105,-1,9,1101,0,0,12,4,12,99,1"),
             0,
         );
-        m2.execute_until_stopped();
-        assert_eq!(m2.output.len(), 1);
-        assert_eq!(m2.output[0], 0);
+        m2.execute_until_stopped().unwrap();
+        assert_eq!(m2.take_output(), vec![0]);
     }
 
     #[test]
     fn test_large() {
         let mut m1 = IntCodeComputer::read_program_with_input(&String::from("3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99"), 7);
-        m1.execute_until_stopped();
-        assert_eq!(m1.output.len(), 1);
-        assert_eq!(m1.output[0], 999);
+        m1.execute_until_stopped().unwrap();
+        assert_eq!(m1.take_output(), vec![999]);
         let mut m2 = IntCodeComputer::read_program_with_input(&String::from("3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99"), 8);
-        m2.execute_until_stopped();
-        assert_eq!(m2.output.len(), 1);
-        assert_eq!(m2.output[0], 1000);
+        m2.execute_until_stopped().unwrap();
+        assert_eq!(m2.take_output(), vec![1000]);
         let mut m3 = IntCodeComputer::read_program_with_input(&String::from("3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99"), 9);
-        m3.execute_until_stopped();
-        assert_eq!(m3.output.len(), 1);
-        assert_eq!(m3.output[0], 1001);
+        m3.execute_until_stopped().unwrap();
+        assert_eq!(m3.take_output(), vec![1001]);
     }
 
     #[test]
@@ -386,19 +1044,169 @@ mod tests {
         let mut m1 = IntCodeComputer::read_program(&String::from(
             "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99",
         ));
-        m1.execute_until_stopped();
+        m1.execute_until_stopped().unwrap();
         assert_eq!(
-            m1.output,
+            m1.take_output(),
             vec![109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99]
         );
         let mut m2 =
             IntCodeComputer::read_program(&String::from("1102,34915192,34915192,7,4,7,99,0"));
-        m2.execute_until_stopped();
-        assert_eq!(m2.output.len(), 1);
-        assert_eq!(m2.output[0], 1219070632396864);
+        m2.execute_until_stopped().unwrap();
+        assert_eq!(m2.take_output(), vec![1219070632396864]);
         let mut m3 = IntCodeComputer::read_program(&String::from("104,1125899906842624,99"));
-        m3.execute_until_stopped();
-        assert_eq!(m3.output.len(), 1);
-        assert_eq!(m3.output[0], 1125899906842624);
+        m3.execute_until_stopped().unwrap();
+        assert_eq!(m3.take_output(), vec![1125899906842624]);
+    }
+
+    #[test]
+    fn test_invalid_opcode() {
+        let mut m = IntCodeComputer::read_program(&String::from("1101,1,1,5,42,0"));
+        let error = m.execute_until_stopped().unwrap_err();
+        assert_eq!(
+            error,
+            IntcodeError::InvalidOpcode {
+                ip: 4,
+                instruction: 42
+            }
+        );
+        assert_eq!(m.current_op, 4);
+        assert_eq!(m.get_value(5), 2);
+        assert_eq!(m.execute_until_stopped(), Err(error));
+    }
+
+    #[test]
+    fn test_invalid_mode() {
+        let mut m = IntCodeComputer::read_program(&String::from("301,0,0,0,99"));
+        assert_eq!(
+            m.execute_until_stopped(),
+            Err(IntcodeError::InvalidMode {
+                ip: 0,
+                instruction: 301,
+                mode: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_write_to_immediate() {
+        let mut m = IntCodeComputer::read_program(&String::from("11101,1,1,0,99"));
+        assert_eq!(
+            m.execute_until_stopped(),
+            Err(IntcodeError::WriteToImmediate {
+                ip: 0,
+                instruction: 11101
+            })
+        );
+        assert_eq!(m.get_value(0), 11101);
+    }
+
+    #[test]
+    fn test_negative_address() {
+        let mut m1 = IntCodeComputer::read_program(&String::from("109,-5,204,1,99"));
+        assert_eq!(
+            m1.execute_until_stopped(),
+            Err(IntcodeError::NegativeAddress {
+                ip: 2,
+                instruction: 204,
+                address: -4
+            })
+        );
+        let mut m2 = IntCodeComputer::read_program(&String::from("1105,1,-1"));
+        assert_eq!(
+            m2.execute_until_stopped(),
+            Err(IntcodeError::NegativeAddress {
+                ip: 0,
+                instruction: 1105,
+                address: -1
+            })
+        );
+    }
+
+    #[test]
+    fn test_far_memory_and_limit() {
+        let program = "1101,3,4,1000000000,4,1000000000,99".to_string();
+        let mut m = IntCodeComputer::read_program(&program);
+        m.execute_until_halted().unwrap();
+        assert_eq!(m.take_output(), vec![7]);
+        assert_eq!(m.get_memory().len(), 7);
+
+        let mut m = IntCodeComputer::read_program(&program);
+        m.set_memory_limit(Some(1024));
+        assert_eq!(
+            m.execute_until_halted(),
+            Err(IntcodeError::MemoryLimit {
+                ip: 0,
+                instruction: 1101,
+                address: 1000000000
+            })
+        );
+    }
+
+    #[test]
+    fn test_cycle_budget() {
+        let mut m = IntCodeComputer::read_program("1001,12,1,12,1008,12,500,13,1006,13,0,99,0,0");
+        assert_eq!(
+            m.execute_until_stopped_within(Budget::cycles(100)),
+            Ok(Outcome::BudgetExhausted)
+        );
+        assert_eq!(m.get_value(12), 34);
+        assert_eq!(
+            m.execute_until_stopped_within(Budget::cycles(10_000)),
+            Ok(Outcome::Halted)
+        );
+        assert_eq!(m.get_value(12), 500);
+
+        let mut m = IntCodeComputer::read_program("3,0,99");
+        assert_eq!(
+            m.execute_until_stopped_within(Budget::cycles(10)),
+            Ok(Outcome::Waiting)
+        );
+    }
+
+    #[test]
+    fn test_deadline() {
+        let mut m = IntCodeComputer::read_program("1105,1,0");
+        let budget = Budget::default().with_timeout(Duration::from_millis(20));
+        assert_eq!(
+            m.execute_until_stopped_within(budget),
+            Ok(Outcome::BudgetExhausted)
+        );
+        assert!(!m.has_terminated());
+    }
+
+    #[test]
+    fn test_input_exhausted() {
+        let mut m = IntCodeComputer::read_program(&String::from("3,0,3,0,99"));
+        m.add_input(1);
+        assert_eq!(m.execute_until_stopped(), Ok(()));
+        assert_eq!(
+            m.execute_until_halted(),
+            Err(IntcodeError::InputExhausted {
+                ip: 2,
+                instruction: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_fuzz_regressions() {
+        let words = |text: &str| -> Vec<i64> {
+            text.split(',')
+                .filter(|x| !x.is_empty())
+                .map(|x| x.trim().parse().unwrap())
+                .collect()
+        };
+        let cases = include_str!("../data/fuzz_regressions.txt")
+            .lines()
+            .filter(|x| !x.is_empty() && !x.starts_with('#'));
+        for case in cases {
+            let (program, input) = case.split_once(';').unwrap();
+            assert_eq!(
+                crate::intcode::fuzz::check(&words(program), &words(input)),
+                Ok(()),
+                "{}",
+                case
+            );
+        }
     }
 }
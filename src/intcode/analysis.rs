@@ -0,0 +1,405 @@
+use crate::intcode::disassembler::is_unconditional_jump;
+use crate::intcode::disassembler::return_address;
+use crate::intcode::Op;
+use crate::intcode::Parameter;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EdgeKind {
+    /// Execution continues with the next instruction.
+    FallThrough,
+    /// Unconditional jump.
+    Jump,
+    /// Conditional jump that was taken.
+    Branch,
+    /// Jump into a subroutine after pushing a return address on the relative stack.
+    Call,
+    /// Where a subroutine called from the block returns to.
+    Return,
+}
+
+impl EdgeKind {
+    fn dot_attributes(&self) -> &'static str {
+        match self {
+            EdgeKind::FallThrough => "",
+            EdgeKind::Jump => " [label=\"jump\"]",
+            EdgeKind::Branch => " [label=\"branch\"]",
+            EdgeKind::Call => " [label=\"call\", color=blue]",
+            EdgeKind::Return => " [label=\"return\", style=dashed]",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+pub struct BasicBlock {
+    pub start: usize,
+    pub instructions: Vec<(usize, Op)>,
+    /// The block ends in a jump whose target is only known at run time, such
+    /// as a subroutine return through the relative base.
+    pub indirect: bool,
+}
+
+impl BasicBlock {
+    /// Address just past the last instruction.
+    pub fn end(&self) -> usize {
+        match self.instructions.last() {
+            Some((address, op)) => address + op.size(),
+            None => self.start,
+        }
+    }
+}
+
+/// A store whose position-mode destination lies inside an instruction.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SelfModifyingStore {
+    pub address: usize,
+    pub target: usize,
+}
+
+pub struct ControlFlowGraph {
+    pub blocks: BTreeMap<usize, BasicBlock>,
+    pub edges: Vec<Edge>,
+    pub self_modifying: Vec<SelfModifyingStore>,
+}
+
+/// Resolves jump targets from the program image. Relative-mode stores are
+/// assumed to go to the stack past the image, so a position-mode cell is
+/// constant unless a position-mode store names it.
+struct Resolver<'a> {
+    memory: &'a [i64],
+    written: BTreeSet<usize>,
+}
+
+impl<'a> Resolver<'a> {
+    fn target(&self, parameter: &Parameter) -> Option<usize> {
+        let value = match parameter {
+            Parameter::Immediate(x) => *x,
+            Parameter::Position(x) => {
+                let address = usize::try_from(*x).ok()?;
+                if self.written.contains(&address) || address >= self.memory.len() {
+                    return None;
+                }
+                self.memory[address]
+            }
+            Parameter::Relative(_) => return None,
+        };
+        usize::try_from(value).ok()
+    }
+
+    /// Successors of one instruction, and whether it jumps somewhere unknown.
+    fn successors(&self, address: usize, op: &Op) -> (Vec<(usize, EdgeKind)>, bool) {
+        let next = address + op.size();
+        match op {
+            Op::Halt => (vec![], false),
+            Op::JumpIfTrue(_, target) | Op::JumpIfFalse(_, target) => {
+                let unconditional = is_unconditional_jump(op);
+                let kind = if unconditional {
+                    EdgeKind::Jump
+                } else {
+                    EdgeKind::Branch
+                };
+                let mut result = Vec::new();
+                if !unconditional {
+                    result.push((next, EdgeKind::FallThrough));
+                }
+                let resolved = self.target(target);
+                result.extend(resolved.map(|x| (x, kind)));
+                (result, resolved.is_none())
+            }
+            _ => {
+                let mut result = vec![(next, EdgeKind::FallThrough)];
+                result.extend(
+                    return_address(self.memory, address, op).map(|x| (x, EdgeKind::Return)),
+                );
+                (result, false)
+            }
+        }
+    }
+
+    /// Instructions reachable from address 0.
+    fn discover(&self) -> BTreeMap<usize, Op> {
+        let mut code = BTreeMap::new();
+        let mut pending = vec![0];
+        while let Some(address) = pending.pop() {
+            if address >= self.memory.len() || code.contains_key(&address) {
+                continue;
+            }
+            let op = match Op::decode(self.memory, address) {
+                Ok(op) => op,
+                Err(_) => continue,
+            };
+            pending.extend(self.successors(address, &op).0.iter().map(|x| x.0));
+            code.insert(address, op);
+        }
+        code
+    }
+}
+
+fn ends_block(op: &Op) -> bool {
+    matches!(op, Op::JumpIfTrue(..) | Op::JumpIfFalse(..) | Op::Halt)
+}
+
+fn position_writes(code: &BTreeMap<usize, Op>) -> BTreeSet<usize> {
+    code.values()
+        .filter_map(|op| match op.destination() {
+            Some(Parameter::Position(x)) => usize::try_from(*x).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+impl ControlFlowGraph {
+    pub fn new(memory: &[i64]) -> ControlFlowGraph {
+        // Code found through constant jumps alone tells which cells are written,
+        // which in turn lets jumps through unwritten cells be followed.
+        let mut resolver = Resolver {
+            memory,
+            written: (0..memory.len()).collect(),
+        };
+        resolver.written = position_writes(&resolver.discover());
+        let code = resolver.discover();
+        resolver.written = position_writes(&code);
+
+        let mut code_words = BTreeSet::new();
+        for (address, op) in code.iter() {
+            code_words.extend(*address..address + op.size());
+        }
+        let self_modifying = code
+            .iter()
+            .filter_map(|(address, op)| {
+                match op.destination() {
+                    Some(Parameter::Position(x)) => usize::try_from(*x).ok(),
+                    _ => None,
+                }
+                .filter(|target| code_words.contains(target))
+                .map(|target| SelfModifyingStore {
+                    address: *address,
+                    target,
+                })
+            })
+            .collect();
+
+        // Blocks start at the entry point and at every jump target or return
+        // address, and end after every jump or halt
+        let mut leaders = BTreeSet::from([0]);
+        for (address, op) in code.iter() {
+            let (successors, _) = resolver.successors(*address, op);
+            for (target, kind) in successors {
+                if kind != EdgeKind::FallThrough {
+                    leaders.insert(target);
+                }
+            }
+            if ends_block(op) {
+                leaders.insert(address + op.size());
+            }
+        }
+
+        let mut blocks: BTreeMap<usize, BasicBlock> = BTreeMap::new();
+        let mut edges = Vec::new();
+        let mut current: Option<BasicBlock> = None;
+        for (address, op) in code.iter() {
+            let continues = current
+                .as_ref()
+                .is_some_and(|block| block.end() == *address);
+            if leaders.contains(address) || !continues {
+                if let Some(block) = current.take() {
+                    if continues {
+                        edges.push(Edge {
+                            from: block.start,
+                            to: *address,
+                            kind: EdgeKind::FallThrough,
+                        });
+                    }
+                    blocks.insert(block.start, block);
+                }
+                current = Some(BasicBlock {
+                    start: *address,
+                    instructions: Vec::new(),
+                    indirect: false,
+                });
+            }
+            let block = current.as_mut().unwrap();
+            block.instructions.push((*address, *op));
+            let (successors, indirect) = resolver.successors(*address, op);
+            block.indirect |= indirect;
+            for (target, kind) in successors {
+                if kind == EdgeKind::FallThrough {
+                    continue;
+                }
+                let pushed_return = match block.instructions.iter().rev().nth(1) {
+                    Some((push_address, push)) => return_address(memory, *push_address, push),
+                    None => None,
+                };
+                let is_call = kind == EdgeKind::Jump && pushed_return == Some(address + op.size());
+                edges.push(Edge {
+                    from: block.start,
+                    to: target,
+                    kind: if is_call { EdgeKind::Call } else { kind },
+                });
+            }
+            if ends_block(op) {
+                let block = current.take().unwrap();
+                if !is_unconditional_jump(op) && *op != Op::Halt {
+                    edges.push(Edge {
+                        from: block.start,
+                        to: address + op.size(),
+                        kind: EdgeKind::FallThrough,
+                    });
+                }
+                blocks.insert(block.start, block);
+            }
+        }
+        if let Some(block) = current.take() {
+            blocks.insert(block.start, block);
+        }
+
+        ControlFlowGraph {
+            blocks,
+            edges,
+            self_modifying,
+        }
+    }
+
+    /// Block starting addresses of subroutines entered through the call idiom.
+    pub fn subroutines(&self) -> BTreeSet<usize> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.kind == EdgeKind::Call)
+            .map(|edge| edge.to)
+            .collect()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let subroutines = self.subroutines();
+        let modified: BTreeSet<usize> = self.self_modifying.iter().map(|x| x.target).collect();
+        let mut output =
+            String::from("digraph intcode {\n    node [shape=box, fontname=\"monospace\"];\n");
+        for block in self.blocks.values() {
+            let mut label = String::new();
+            for (address, op) in block.instructions.iter() {
+                label.push_str(&format!("{}: {}\\l", address, op));
+            }
+            if block.indirect {
+                label.push_str("-> indirect\\l");
+            }
+            let mut attributes = String::new();
+            if subroutines.contains(&block.start) {
+                attributes.push_str(", peripheries=2");
+            }
+            if (block.start..block.end()).any(|x| modified.contains(&x)) {
+                attributes.push_str(", color=red");
+            }
+            output.push_str(&format!(
+                "    b{} [label=\"{}\"{}];\n",
+                block.start, label, attributes
+            ));
+        }
+        for edge in self.edges.iter() {
+            output.push_str(&format!(
+                "    b{} -> b{}{};\n",
+                edge.from,
+                edge.to,
+                edge.kind.dot_attributes()
+            ));
+        }
+        output.push_str("}\n");
+        output
+    }
+
+    pub fn render_summary(&self) -> String {
+        let mut output = format!(
+            "{} blocks, {} edges, {} subroutines, {} indirect jumps\n",
+            self.blocks.len(),
+            self.edges.len(),
+            self.subroutines().len(),
+            self.blocks.values().filter(|x| x.indirect).count()
+        );
+        for store in self.self_modifying.iter() {
+            output.push_str(&format!(
+                "Self-modifying store at {} writes code at {}\n",
+                store.address, store.target
+            ));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input_files::read_content;
+    use crate::intcode::analysis::ControlFlowGraph;
+    use crate::intcode::analysis::Edge;
+    use crate::intcode::analysis::EdgeKind;
+    use crate::intcode::analysis::SelfModifyingStore;
+    use crate::intcode::IntCodeComputer;
+
+    fn edge(from: usize, to: usize, kind: EdgeKind) -> Edge {
+        Edge { from, to, kind }
+    }
+
+    #[test]
+    fn test_blocks_and_branches() {
+        // Reads a value into 12 and outputs 1 if it is non-zero, 0 otherwise
+        let graph = ControlFlowGraph::new(&[3, 12, 1006, 12, 9, 104, 1, 99, 0, 104, 0, 99, 0]);
+        let starts: Vec<usize> = graph.blocks.keys().copied().collect();
+        assert_eq!(starts, vec![0, 5, 9]);
+        assert_eq!(
+            graph.edges,
+            vec![
+                edge(0, 9, EdgeKind::Branch),
+                edge(0, 5, EdgeKind::FallThrough)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_position_mode_targets() {
+        // JNZ #1, [7] jumps to the address stored in the never written cell 7
+        let graph = ControlFlowGraph::new(&[1105, 1, 3, 105, 1, 7, 99, 9, 1, 99]);
+        assert!(graph.edges.contains(&edge(3, 9, EdgeKind::Jump)));
+        assert!(graph.blocks.values().all(|x| !x.indirect));
+
+        // Once the cell is written the target is no longer constant
+        let graph = ControlFlowGraph::new(&[1101, 0, 9, 8, 105, 1, 8, 99, 0, 99]);
+        assert!(graph.blocks[&0].indirect);
+    }
+
+    #[test]
+    fn test_calls_and_self_modification() {
+        // Calls the subroutine at 14, which returns through the relative base
+        // to 9, where the instruction at 0 is overwritten
+        let graph = ControlFlowGraph::new(&[
+            109, 20, 21101, 9, 0, 0, 1105, 1, 14, 1101, 0, 0, 0, 99, 2106, 0, 0,
+        ]);
+        assert!(graph.edges.contains(&edge(0, 14, EdgeKind::Call)));
+        assert!(graph.edges.contains(&edge(0, 9, EdgeKind::Return)));
+        assert!(graph.blocks[&14].indirect);
+        assert_eq!(
+            graph.self_modifying,
+            vec![SelfModifyingStore {
+                address: 9,
+                target: 0
+            }]
+        );
+        let dot = graph.to_dot();
+        assert!(dot.contains("b14 [label=\"14: JZ #0, rb+0\\l-> indirect\\l\", peripheries=2];"));
+        assert!(dot.contains("b0 -> b14 [label=\"call\", color=blue];"));
+    }
+
+    #[test]
+    fn test_boost_program() {
+        let content = read_content("data/day09.txt").unwrap();
+        let memory = IntCodeComputer::read_program(&content).get_memory();
+        let graph = ControlFlowGraph::new(&memory);
+        assert!(graph.blocks.len() > 10);
+        assert!(!graph.subroutines().is_empty());
+        assert!(graph.to_dot().starts_with("digraph intcode {"));
+    }
+}
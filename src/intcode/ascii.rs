@@ -0,0 +1,183 @@
+use crate::intcode::IntCodeComputer;
+use crate::intcode::IntcodeError;
+use crate::intcode::State;
+use std::io::BufRead;
+use std::io::Write;
+
+/// Output codes above this are results rather than characters.
+const MAX_ASCII: i64 = 127;
+
+/// A run of decoded output.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AsciiOutput {
+    Text(String),
+    /// A value outside the ASCII range, such as a puzzle answer.
+    Value(i64),
+}
+
+/// The input codes for a line of text, including the terminating newline.
+pub fn encode_line(line: &str) -> Vec<i64> {
+    line.bytes()
+        .chain(std::iter::once(b'\n'))
+        .map(|x| x as i64)
+        .collect()
+}
+
+/// Groups output codes into runs of text, passing other values through.
+pub fn decode(values: &[i64]) -> Vec<AsciiOutput> {
+    let mut decoded = Vec::new();
+    let mut text = String::new();
+    for value in values {
+        if (0..=MAX_ASCII).contains(value) {
+            text.push(*value as u8 as char);
+            continue;
+        }
+        if !text.is_empty() {
+            decoded.push(AsciiOutput::Text(std::mem::take(&mut text)));
+        }
+        decoded.push(AsciiOutput::Value(*value));
+    }
+    if !text.is_empty() {
+        decoded.push(AsciiOutput::Text(text));
+    }
+    decoded
+}
+
+/// Talks to a machine in lines of text instead of individual codes.
+pub struct AsciiConsole {
+    computer: IntCodeComputer,
+}
+
+impl AsciiConsole {
+    pub fn new(computer: IntCodeComputer) -> AsciiConsole {
+        AsciiConsole { computer }
+    }
+
+    pub fn send_line(&mut self, line: &str) {
+        for code in encode_line(line) {
+            self.computer.add_input(code);
+        }
+    }
+
+    /// Runs until the machine halts or wants more input, and decodes what it printed.
+    pub fn run(&mut self) -> Result<Vec<AsciiOutput>, IntcodeError> {
+        self.computer.execute_until_stopped()?;
+        Ok(decode(&self.computer.take_output()))
+    }
+
+    pub fn has_terminated(&self) -> bool {
+        self.computer.has_terminated()
+    }
+
+    /// Faults the machine, so that output it printed but nobody saw is not
+    /// mistaken for a normal stop.
+    fn output_failed(&mut self, error: std::io::Error) -> IntcodeError {
+        let error = IntcodeError::OutputFailed {
+            ip: self.computer.current_op,
+            message: error.to_string(),
+        };
+        self.computer.state = State::Faulted(error.clone());
+        error
+    }
+
+    /// Alternates between printing the machine's output to `output` and
+    /// feeding it lines from `input`, until it halts or the input ends.
+    pub fn run_interactive<R: BufRead, W: Write>(
+        &mut self,
+        input: R,
+        mut output: W,
+    ) -> Result<(), IntcodeError> {
+        let mut lines = input.lines();
+        loop {
+            for chunk in self.run()? {
+                match chunk {
+                    AsciiOutput::Text(text) => write!(output, "{}", text),
+                    AsciiOutput::Value(value) => writeln!(output, "{}", value),
+                }
+                .map_err(|error| self.output_failed(error))?;
+            }
+            output.flush().map_err(|error| self.output_failed(error))?;
+            if self.has_terminated() {
+                return Ok(());
+            }
+            match lines.next() {
+                Some(Ok(line)) => self.send_line(line.trim_end()),
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::ascii::decode;
+    use crate::intcode::ascii::encode_line;
+    use crate::intcode::ascii::AsciiConsole;
+    use crate::intcode::ascii::AsciiOutput;
+    use crate::intcode::IntCodeComputer;
+    use crate::intcode::IntcodeError;
+
+    /// Echoes one line of input, then outputs 1000 and halts.
+    const ECHO: &str = "3,100,4,100,1008,100,10,101,1006,101,0,104,1000,99";
+
+    #[test]
+    fn test_encode_and_decode() {
+        assert_eq!(encode_line("Hi"), vec![72, 105, 10]);
+        assert_eq!(
+            decode(&[72, 105, 10, 500, 33]),
+            vec![
+                AsciiOutput::Text("Hi\n".to_string()),
+                AsciiOutput::Value(500),
+                AsciiOutput::Text("!".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_console() {
+        let mut console = AsciiConsole::new(IntCodeComputer::read_program(ECHO));
+        assert_eq!(console.run().unwrap(), vec![]);
+        console.send_line("hello");
+        assert_eq!(
+            console.run().unwrap(),
+            vec![
+                AsciiOutput::Text("hello\n".to_string()),
+                AsciiOutput::Value(1000)
+            ]
+        );
+        assert!(console.has_terminated());
+    }
+
+    #[test]
+    fn test_interactive() {
+        let mut console = AsciiConsole::new(IntCodeComputer::read_program(ECHO));
+        let mut output = Vec::new();
+        console
+            .run_interactive("walk\nignored\n".as_bytes(), &mut output)
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "walk\n1000\n");
+    }
+
+    #[test]
+    fn test_interactive_write_error() {
+        /// Fails like a pipe whose reader has gone away.
+        struct Closed;
+        impl std::io::Write for Closed {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut console = AsciiConsole::new(IntCodeComputer::read_program(ECHO));
+        assert_eq!(
+            console.run_interactive("walk\n".as_bytes(), Closed),
+            Err(IntcodeError::OutputFailed {
+                ip: 13,
+                message: "broken pipe".to_string()
+            })
+        );
+        assert!(console.run().is_err());
+    }
+}
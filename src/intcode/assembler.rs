@@ -0,0 +1,333 @@
+use crate::intcode::Op;
+use crate::intcode::Parameter;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct AssemblyError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AssemblyError {}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Number(i64),
+    Label(String, i64),
+}
+
+#[derive(Debug, Clone)]
+struct Operand {
+    mode: i64,
+    value: Value,
+}
+
+#[derive(Debug)]
+enum Statement {
+    Instruction(String, Vec<Operand>),
+    Data(Vec<Value>),
+}
+
+impl Statement {
+    fn size(&self) -> usize {
+        match self {
+            Statement::Instruction(_, operands) => operands.len() + 1,
+            Statement::Data(values) => values.len(),
+        }
+    }
+}
+
+fn arity(mnemonic: &str) -> Option<usize> {
+    match mnemonic {
+        "ADD" | "MUL" | "LT" | "EQ" => Some(3),
+        "JNZ" | "JZ" => Some(2),
+        "IN" | "OUT" | "ARB" => Some(1),
+        "HLT" => Some(0),
+        _ => None,
+    }
+}
+
+fn build_op(mnemonic: &str, parameters: Vec<Parameter>) -> Op {
+    let mut parameters = parameters.into_iter();
+    let mut next = || parameters.next().unwrap();
+    match mnemonic {
+        "ADD" => Op::Add(next(), next(), next()),
+        "MUL" => Op::Multiply(next(), next(), next()),
+        "IN" => Op::Input(next()),
+        "OUT" => Op::Output(next()),
+        "JNZ" => Op::JumpIfTrue(next(), next()),
+        "JZ" => Op::JumpIfFalse(next(), next()),
+        "LT" => Op::LessThan(next(), next(), next()),
+        "EQ" => Op::Equals(next(), next(), next()),
+        "ARB" => Op::AdjustRelativeBase(next()),
+        "HLT" => Op::Halt,
+        _ => panic!("Unknown mnemonic {}", mnemonic),
+    }
+}
+
+struct Parser {
+    listing_prefix: Regex,
+    label: Regex,
+    symbol: Regex,
+}
+
+impl Parser {
+    fn new() -> Parser {
+        Parser {
+            listing_prefix: Regex::new(r"^\d+:\s+(?:[-\d,]+\s+)?").unwrap(),
+            label: Regex::new(r"^([A-Za-z_]\w*):\s*").unwrap(),
+            symbol: Regex::new(r"^([A-Za-z_]\w*)(?:([+-])(\d+))?$").unwrap(),
+        }
+    }
+
+    fn parse_value(&self, text: &str) -> Option<Value> {
+        if let Ok(number) = text.parse::<i64>() {
+            return Some(Value::Number(number));
+        }
+        let matched = self.symbol.captures(text)?;
+        let mut offset = match matched.get(3) {
+            Some(x) => x.as_str().parse::<i64>().ok()?,
+            None => 0,
+        };
+        if matched.get(2).map(|x| x.as_str()) == Some("-") {
+            offset = -offset;
+        }
+        Some(Value::Label(matched[1].to_string(), offset))
+    }
+
+    fn parse_operand(&self, text: &str) -> Option<Operand> {
+        if let Some(inner) = text.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+            Some(Operand {
+                mode: 0,
+                value: self.parse_value(inner.trim())?,
+            })
+        } else if let Some(inner) = text.strip_prefix('#') {
+            Some(Operand {
+                mode: 1,
+                value: self.parse_value(inner.trim())?,
+            })
+        } else if let Some(inner) = text.strip_prefix("rb") {
+            let inner = inner.trim();
+            let offset = if inner.is_empty() {
+                0
+            } else {
+                inner
+                    .strip_prefix('+')
+                    .unwrap_or(inner)
+                    .parse::<i64>()
+                    .ok()?
+            };
+            Some(Operand {
+                mode: 2,
+                value: Value::Number(offset),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Splits a line into the labels it defines and the statement it contains, if any.
+    fn parse_line(&self, line: &str) -> Result<(Vec<String>, Option<Statement>), String> {
+        let mut rest = line.split(';').next().unwrap().trim();
+        if let Some(matched) = self.listing_prefix.find(rest) {
+            rest = &rest[matched.end()..];
+        }
+        let mut labels = Vec::new();
+        while let Some(matched) = self.label.captures(rest) {
+            labels.push(matched[1].to_string());
+            rest = &rest[matched.get(0).unwrap().end()..];
+        }
+        if rest.is_empty() {
+            return Ok((labels, None));
+        }
+
+        let (mnemonic, arguments) = match rest.find(char::is_whitespace) {
+            Some(index) => (&rest[..index], rest[index..].trim()),
+            None => (rest, ""),
+        };
+        let arguments: Vec<&str> = if arguments.is_empty() {
+            Vec::new()
+        } else {
+            arguments.split(',').map(|x| x.trim()).collect()
+        };
+        let mnemonic = mnemonic.to_uppercase();
+        if mnemonic == ".DATA" || mnemonic == "DATA" {
+            let mut values = Vec::new();
+            for argument in arguments {
+                values.push(
+                    self.parse_value(argument)
+                        .ok_or(format!("Invalid data value '{}'", argument))?,
+                );
+            }
+            return Ok((labels, Some(Statement::Data(values))));
+        }
+
+        let expected = arity(&mnemonic).ok_or(format!("Unknown mnemonic '{}'", mnemonic))?;
+        if arguments.len() != expected {
+            return Err(format!(
+                "{} expects {} operands, got {}",
+                mnemonic,
+                expected,
+                arguments.len()
+            ));
+        }
+        let mut operands = Vec::new();
+        for argument in arguments {
+            operands.push(
+                self.parse_operand(argument)
+                    .ok_or(format!("Invalid operand '{}'", argument))?,
+            );
+        }
+        Ok((labels, Some(Statement::Instruction(mnemonic, operands))))
+    }
+}
+
+fn resolve(value: &Value, labels: &HashMap<String, usize>) -> Result<i64, String> {
+    match value {
+        Value::Number(x) => Ok(*x),
+        Value::Label(name, offset) => labels
+            .get(name)
+            .map(|x| *x as i64 + offset)
+            .ok_or(format!("Undefined label '{}'", name)),
+    }
+}
+
+/// Assembles source text into Intcode memory words.
+///
+/// Lines copied from a disassembly listing are accepted as-is: the leading
+/// address and raw word columns are skipped.
+pub fn assemble(source: &str) -> Result<Vec<i64>, AssemblyError> {
+    let parser = Parser::new();
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    let mut address = 0;
+    for (index, line) in source.lines().enumerate() {
+        let (defined, statement) = parser.parse_line(line).map_err(|message| AssemblyError {
+            line: index + 1,
+            message,
+        })?;
+        for label in defined {
+            if labels.insert(label.clone(), address).is_some() {
+                return Err(AssemblyError {
+                    line: index + 1,
+                    message: format!("Duplicate label '{}'", label),
+                });
+            }
+        }
+        if let Some(statement) = statement {
+            address += statement.size();
+            statements.push((index + 1, statement));
+        }
+    }
+
+    let mut words = Vec::new();
+    for (line, statement) in statements {
+        let error = |message| AssemblyError { line, message };
+        match statement {
+            Statement::Data(values) => {
+                for value in values.iter() {
+                    words.push(resolve(value, &labels).map_err(error)?);
+                }
+            }
+            Statement::Instruction(mnemonic, operands) => {
+                let mut parameters = Vec::new();
+                for operand in operands.iter() {
+                    let value = resolve(&operand.value, &labels).map_err(error)?;
+                    parameters.push(Parameter::from(operand.mode, value).unwrap());
+                }
+                words.extend(build_op(&mnemonic, parameters).encode());
+            }
+        }
+    }
+
+    Ok(words)
+}
+
+/// Renders memory words in the comma-separated format read by `IntCodeComputer::read_program`.
+pub fn to_program_text(words: &[i64]) -> String {
+    words
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input_files::read_content;
+    use crate::intcode::assembler::assemble;
+    use crate::intcode::assembler::to_program_text;
+    use crate::intcode::assembler::AssemblyError;
+    use crate::intcode::disassembler::Listing;
+    use crate::intcode::IntCodeComputer;
+
+    #[test]
+    fn test_labels_and_data() {
+        let source = "
+            ; Prints its input doubled
+                  IN [value]
+                  MUL [value], #2, [value]
+            loop: OUT [value]
+                  JZ #0, #end
+                  .data 1, 2
+            end:  HLT
+            value: .data 0
+        ";
+        let words = assemble(source).unwrap();
+        assert_eq!(
+            to_program_text(&words),
+            "3,14,1002,14,2,14,4,14,1106,0,13,1,2,99,0"
+        );
+        assert_eq!(words.len(), 15);
+    }
+
+    #[test]
+    fn test_relative_operands() {
+        let words = assemble("ARB #5\nOUT rb-2\nADD rb+1, rb, rb+3\nHLT").unwrap();
+        assert_eq!(words, vec![109, 5, 204, -2, 22201, 1, 0, 3, 99]);
+    }
+
+    #[test]
+    fn test_forward_reference_with_offset() {
+        let words = assemble("JNZ #1, #target+1\ntarget: .data 5, 99").unwrap();
+        assert_eq!(words, vec![1105, 1, 4, 5, 99]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            assemble("ADD #1, #2\n"),
+            Err(AssemblyError {
+                line: 1,
+                message: "ADD expects 3 operands, got 2".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("HLT\nJZ #0, #nowhere"),
+            Err(AssemblyError {
+                line: 2,
+                message: "Undefined label 'nowhere'".to_string()
+            })
+        );
+        assert!(assemble("FOO [1]").is_err());
+        assert!(assemble("a: HLT\na: HLT").is_err());
+    }
+
+    #[test]
+    fn test_round_trip_of_puzzle_programs() {
+        for day in ["02", "05", "07", "09", "11", "13", "15"] {
+            let content = read_content(&format!("data/day{}.txt", day)).unwrap();
+            let m = IntCodeComputer::read_program(&content);
+            let listing = Listing::new(&m.get_memory()).render();
+            assert_eq!(assemble(&listing).unwrap(), m.get_memory(), "day {}", day);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::VecDeque;
+use std::io::BufRead;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+
+/// A source of values for the `IN` instruction.
+pub trait IntcodeInput {
+    /// Returns the next value, or `None` if the machine has to wait for more.
+    fn next_input(&mut self) -> Option<i64>;
+}
+
+/// A sink for values produced by the `OUT` instruction.
+pub trait IntcodeOutput {
+    fn write_output(&mut self, value: i64);
+}
+
+impl IntcodeInput for VecDeque<i64> {
+    fn next_input(&mut self) -> Option<i64> {
+        self.pop_front()
+    }
+}
+
+impl IntcodeOutput for VecDeque<i64> {
+    fn write_output(&mut self, value: i64) {
+        self.push_back(value);
+    }
+}
+
+impl IntcodeOutput for Vec<i64> {
+    fn write_output(&mut self, value: i64) {
+        self.push(value);
+    }
+}
+
+/// Blocks until a value arrives. A closed channel makes the machine wait.
+impl IntcodeInput for Receiver<i64> {
+    fn next_input(&mut self) -> Option<i64> {
+        self.recv().ok()
+    }
+}
+
+/// Values sent after the receiving end has been dropped are discarded.
+impl IntcodeOutput for Sender<i64> {
+    fn write_output(&mut self, value: i64) {
+        let _ = self.send(value);
+    }
+}
+
+pub struct InputFn<F: FnMut() -> Option<i64>>(pub F);
+
+impl<F: FnMut() -> Option<i64>> IntcodeInput for InputFn<F> {
+    fn next_input(&mut self) -> Option<i64> {
+        (self.0)()
+    }
+}
+
+pub struct OutputFn<F: FnMut(i64)>(pub F);
+
+impl<F: FnMut(i64)> IntcodeOutput for OutputFn<F> {
+    fn write_output(&mut self, value: i64) {
+        (self.0)(value)
+    }
+}
+
+/// Reads one integer per line from stdin. End of input makes the machine wait.
+pub struct StdinInput;
+
+impl IntcodeInput for StdinInput {
+    fn next_input(&mut self) -> Option<i64> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if std::io::stdin().lock().read_line(&mut line).ok()? == 0 {
+                return None;
+            }
+            match line.trim().parse::<i64>() {
+                Ok(value) => return Some(value),
+                Err(_) => eprintln!("Not a number: '{}'", line.trim()),
+            }
+        }
+    }
+}
+
+/// Prints one value per line to stdout.
+pub struct StdoutOutput;
+
+impl IntcodeOutput for StdoutOutput {
+    fn write_output(&mut self, value: i64) {
+        println!("{}", value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::channels::InputFn;
+    use crate::intcode::channels::OutputFn;
+    use crate::intcode::IntCodeComputer;
+    use std::collections::VecDeque;
+    use std::sync::mpsc::channel;
+
+    const DOUBLER: &str = "3,9,1002,9,2,9,4,9,1105,1,0";
+
+    #[test]
+    fn test_queue_channels() {
+        let mut m = IntCodeComputer::read_program(DOUBLER);
+        let mut input = VecDeque::from(vec![1, 2, 3]);
+        let mut output = Vec::new();
+        m.execute_with(&mut input, &mut output).unwrap();
+        assert_eq!(output, vec![2, 4, 6]);
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn test_closure_channels() {
+        let mut m = IntCodeComputer::read_program(DOUBLER);
+        let mut next = 0;
+        let mut sum = 0;
+        m.execute_with(
+            &mut InputFn(|| {
+                next += 1;
+                if next <= 4 {
+                    Some(next)
+                } else {
+                    None
+                }
+            }),
+            &mut OutputFn(|x| sum += x),
+        )
+        .unwrap();
+        assert_eq!(sum, 20);
+    }
+
+    #[test]
+    fn test_thread_channels() {
+        let (input_sender, mut input_receiver) = channel();
+        let (mut output_sender, output_receiver) = channel();
+        let worker = std::thread::spawn(move || {
+            let mut m = IntCodeComputer::read_program(DOUBLER);
+            m.execute_with(&mut input_receiver, &mut output_sender)
+                .unwrap();
+        });
+        input_sender.send(21).unwrap();
+        assert_eq!(output_receiver.recv().unwrap(), 42);
+        input_sender.send(-5).unwrap();
+        assert_eq!(output_receiver.recv().unwrap(), -10);
+        drop(input_sender);
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn test_run_until_output() {
+        let mut m = IntCodeComputer::read_program(DOUBLER);
+        m.add_input(1);
+        m.add_input(2);
+        assert_eq!(m.run_until_output(1).unwrap(), vec![2]);
+        assert_eq!(m.run_until_output(2).unwrap(), vec![4]);
+        m.add_input(3);
+        assert_eq!(m.run_until_output(1).unwrap(), vec![6]);
+        assert_eq!(m.get_last_output(), Some(6));
+        let mut halting = IntCodeComputer::read_program("104,1,104,2,99");
+        assert_eq!(halting.run_until_output(5).unwrap(), vec![1, 2]);
+        assert!(halting.has_terminated());
+    }
+}
@@ -0,0 +1,155 @@
+use crate::intcode::disassembler::Listing;
+use crate::intcode::trace::TraceEvent;
+use crate::intcode::trace::Tracer;
+use crate::intcode::Op;
+use crate::intcode::Parameter;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+/// Which outcomes a branch has had, as [false, true]. For jumps true means
+/// the jump was taken, for comparisons that the comparison held.
+type Directions = [bool; 2];
+
+/// Records the addresses executed and the outcome of every conditional jump
+/// and comparison.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    executed: BTreeSet<usize>,
+    branches: BTreeMap<usize, Directions>,
+}
+
+/// The outcome of a conditional instruction, or `None` if it is not one or its
+/// outcome is fixed by immediate parameters.
+fn branch_outcome(event: &TraceEvent) -> Option<bool> {
+    let operands = &event.operands;
+    match event.op {
+        Op::JumpIfTrue(Parameter::Immediate(_), _)
+        | Op::JumpIfFalse(Parameter::Immediate(_), _) => None,
+        Op::LessThan(Parameter::Immediate(_), Parameter::Immediate(_), _)
+        | Op::Equals(Parameter::Immediate(_), Parameter::Immediate(_), _) => None,
+        Op::JumpIfTrue(..) => Some(operands[0] != 0),
+        Op::JumpIfFalse(..) => Some(operands[0] == 0),
+        Op::LessThan(..) => Some(operands[0] < operands[1]),
+        Op::Equals(..) => Some(operands[0] == operands[1]),
+        _ => None,
+    }
+}
+
+impl Tracer for Coverage {
+    fn trace(&mut self, event: &TraceEvent) {
+        self.executed.insert(event.ip);
+        if let Some(outcome) = branch_outcome(event) {
+            self.branches.entry(event.ip).or_default()[outcome as usize] = true;
+        }
+    }
+}
+
+impl Coverage {
+    /// The note for a branch that has only gone one way.
+    fn one_sided(&self, address: usize, op: &Op) -> Option<&'static str> {
+        let directions = self.branches.get(&address)?;
+        let jump = matches!(op, Op::JumpIfTrue(..) | Op::JumpIfFalse(..));
+        match (directions, jump) {
+            ([true, false], true) => Some("never taken"),
+            ([false, true], true) => Some("always taken"),
+            ([true, false], false) => Some("always false"),
+            ([false, true], false) => Some("always true"),
+            _ => None,
+        }
+    }
+
+    /// The disassembly of `memory` with every line marked: `+` executed, `-`
+    /// not executed and `~` a branch that only went one way. Data lines are
+    /// marked `+` only if something in them was executed as code. Passing the
+    /// memory as it is after the run shows self-modified instructions the way
+    /// they last ran.
+    pub fn render(&self, memory: &[i64]) -> String {
+        let executed: Vec<usize> = self.executed.iter().copied().collect();
+        let listing = Listing::with_entry_points(memory, &executed);
+        let mut output = String::new();
+        let (mut instructions, mut covered) = (0, 0);
+        let (mut branches, mut both_ways) = (0, 0);
+        for entry in listing.entries.iter() {
+            let end = entry.address + entry.words.len();
+            let executed = self.executed.range(entry.address..end).next().is_some();
+            let (marker, note) = match &entry.op {
+                None if executed => ("+", None),
+                None => (" ", None),
+                Some(op) => {
+                    instructions += 1;
+                    covered += executed as usize;
+                    let note = self.one_sided(entry.address, op);
+                    if self.branches.contains_key(&entry.address) {
+                        branches += 1;
+                        both_ways += note.is_none() as usize;
+                    }
+                    match (executed, note) {
+                        (false, _) => ("-", None),
+                        (true, None) => ("+", None),
+                        (true, Some(_)) => ("~", note),
+                    }
+                }
+            };
+            output.push_str(&format!("{} {}", marker, entry.render()));
+            if let Some(note) = note {
+                output.push_str(&format!("  ; {}", note));
+            }
+            output.push('\n');
+        }
+        output.push_str(&format!(
+            "Executed {} of {} instructions, {} of {} executed branches went both ways\n",
+            covered, instructions, both_ways, branches
+        ));
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::coverage::Coverage;
+    use crate::intcode::IntCodeComputer;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    /// Outputs 1 if the input equals 8 and 0 otherwise, with a check that the
+    /// input is not negative that always passes for these inputs.
+    const PROGRAM: &str = "3,20,1007,20,0,21,1005,21,19,1008,20,8,22,4,22,99,0,0,0,99";
+
+    fn coverage(inputs: &[i64]) -> Vec<String> {
+        let coverage = Arc::new(Mutex::new(Coverage::default()));
+        let program = PROGRAM.to_string();
+        for input in inputs {
+            let mut m = IntCodeComputer::read_program_with_input(&program, *input);
+            m.set_tracer(Box::new(coverage.clone()));
+            m.execute_until_halted().unwrap();
+        }
+        let memory = IntCodeComputer::read_program(&program).get_memory();
+        let report = coverage.lock().unwrap().render(&memory);
+        report.lines().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_one_sided_branches() {
+        let report = coverage(&[8]);
+        assert!(report[1].starts_with("~     2:"), "{}", report[1]);
+        assert!(report[1].ends_with("; always false"));
+        assert!(report[2].ends_with("; never taken"));
+        assert!(report[3].ends_with("; always true"));
+        assert!(report[4].starts_with("+    13:"));
+        assert_eq!(
+            report.last().unwrap(),
+            "Executed 6 of 7 instructions, 0 of 3 executed branches went both ways"
+        );
+    }
+
+    #[test]
+    fn test_both_directions() {
+        let report = coverage(&[8, 3]);
+        assert!(report[3].starts_with("+     9:"), "{}", report[3]);
+        assert!(report.iter().any(|x| x.starts_with("-    19:")));
+        assert_eq!(
+            report.last().unwrap(),
+            "Executed 6 of 7 instructions, 1 of 3 executed branches went both ways"
+        );
+    }
+}
@@ -0,0 +1,462 @@
+use crate::intcode::IntCodeComputer;
+use crate::intcode::Op;
+use crate::intcode::State;
+use std::collections::BTreeSet;
+use std::io::BufRead;
+use std::io::Write;
+
+/// Number of instructions that can be undone.
+const HISTORY_CAPACITY: usize = 1_000_000;
+
+const HELP: &str = "Commands:
+  step [n]              execute n instructions (default 1)
+  continue              run until a breakpoint, watchpoint, halt or wait for input
+  back [n]              undo the last n instructions (default 1)
+  rewind <addr>         undo instructions until ip is at an address again
+  who <addr>            show the last instruction that wrote to an address
+  break <addr|MNEMONIC> stop before executing an address or every instruction of a kind
+  watch <addr>          stop after an instruction writes to an address
+  clear                 remove all breakpoints and watchpoints
+  regs                  print ip, relative base, consumed+pending input and state
+  dump <addr> [len]     print memory cells
+  poke <addr> <value>   overwrite a memory cell and clear the undo history
+  input <values...>     queue input values
+  output                print all output produced so far
+  list [addr] [n]       disassemble n instructions (default at ip)
+  save <path>           write a snapshot that 'run' and 'debug' can resume from
+  quit                  leave the debugger";
+
+pub struct Debugger {
+    computer: IntCodeComputer,
+    breakpoints: BTreeSet<usize>,
+    opcode_breakpoints: BTreeSet<i64>,
+    watchpoints: BTreeSet<usize>,
+    output: Vec<i64>,
+}
+
+fn parse_address(text: Option<&str>) -> Result<usize, String> {
+    let text = text.ok_or("Missing address")?;
+    text.parse::<usize>()
+        .map_err(|_| format!("Invalid address '{}'", text))
+}
+
+fn opcode_of(mnemonic: &str) -> Option<i64> {
+    match mnemonic.to_uppercase().as_str() {
+        "ADD" => Some(1),
+        "MUL" => Some(2),
+        "IN" => Some(3),
+        "OUT" => Some(4),
+        "JNZ" => Some(5),
+        "JZ" => Some(6),
+        "LT" => Some(7),
+        "EQ" => Some(8),
+        "ARB" => Some(9),
+        "HLT" => Some(99),
+        _ => None,
+    }
+}
+
+impl Debugger {
+    pub fn new(mut computer: IntCodeComputer) -> Debugger {
+        computer.enable_history(HISTORY_CAPACITY);
+        Debugger {
+            computer,
+            breakpoints: BTreeSet::new(),
+            opcode_breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            output: Vec::new(),
+        }
+    }
+
+    fn describe_current(&self) -> String {
+        let ip = self.computer.current_op;
+        match Op::decode(&self.computer.memory, ip) {
+            Ok(op) => format!("{:>5}: {}", ip, op),
+            Err(_) => format!("{:>5}: DATA {}", ip, self.computer.get_value(ip)),
+        }
+    }
+
+    fn describe_state(&self) -> String {
+        match &self.computer.state {
+            State::Running => "running".to_string(),
+            State::Waiting => "waiting for input".to_string(),
+            State::Yielded => "yielded".to_string(),
+            State::Halted => "halted".to_string(),
+            State::Faulted(error) => format!("faulted: {}", error),
+        }
+    }
+
+    fn at_breakpoint(&self) -> bool {
+        self.breakpoints.contains(&self.computer.current_op)
+            || self
+                .opcode_breakpoints
+                .contains(&(self.computer.current_instruction() % 100))
+    }
+
+    /// Executes a single instruction, returning the watched address it wrote to, if any.
+    fn step(&mut self) -> Option<usize> {
+        let ip = self.computer.current_op;
+        let target = self.computer.write_target();
+        // Faults are reported through the machine state
+        let _ = self.computer.execute_step();
+        let target = target.filter(|x| self.watchpoints.contains(x));
+        if self.computer.current_op != ip {
+            target
+        } else {
+            None
+        }
+    }
+
+    /// Runs at most `limit` instructions and explains why execution stopped.
+    fn run(&mut self, limit: Option<usize>) -> String {
+        if self.computer.state == State::Yielded {
+            self.computer.resume();
+        }
+        let mut executed = 0;
+        let reason = loop {
+            if self.computer.state != State::Running {
+                break self.describe_state();
+            }
+            if executed > 0 && self.at_breakpoint() {
+                break "breakpoint".to_string();
+            }
+            if limit == Some(executed) {
+                break "stepped".to_string();
+            }
+            executed += 1;
+            if let Some(address) = self.step() {
+                break format!(
+                    "watchpoint: [{}] = {}",
+                    address,
+                    self.computer.get_value(address)
+                );
+            }
+        };
+
+        let mut report = String::new();
+        let new_output = self.computer.take_output();
+        if !new_output.is_empty() {
+            self.output.extend(new_output.iter());
+            let new_output: Vec<String> = new_output.iter().map(|x| x.to_string()).collect();
+            report.push_str(&format!("Output: {}\n", new_output.join(", ")));
+        }
+        report.push_str(&format!(
+            "Stopped after {} instructions ({})\n{}",
+            executed,
+            reason,
+            self.describe_current()
+        ));
+        report
+    }
+
+    /// Forgets output that stepping back has undone.
+    fn drop_undone_outputs(&mut self) {
+        let undone = self.computer.take_undone_outputs();
+        self.output
+            .truncate(self.output.len().saturating_sub(undone));
+    }
+
+    fn list(&self, start: usize, count: usize) -> String {
+        let mut lines = Vec::new();
+        let mut address = start;
+        for _ in 0..count {
+            let size = match Op::decode(&self.computer.memory, address) {
+                Ok(op) => {
+                    lines.push(format!("{:>5}: {}", address, op));
+                    op.size()
+                }
+                Err(_) => {
+                    lines.push(format!(
+                        "{:>5}: DATA {}",
+                        address,
+                        self.computer.get_value(address)
+                    ));
+                    1
+                }
+            };
+            // Stops at the end of the address space instead of wrapping to 0
+            address = match address.checked_add(size) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        lines.join("\n")
+    }
+
+    /// Executes one command line and returns the text to show the user.
+    pub fn execute_command(&mut self, line: &str) -> Result<String, String> {
+        let mut words = line.split_whitespace();
+        let command = match words.next() {
+            Some(x) => x,
+            None => return Ok(String::new()),
+        };
+        let arguments: Vec<&str> = words.collect();
+        match command {
+            "step" | "s" => {
+                let count = match arguments.first() {
+                    Some(x) => x.parse::<usize>().map_err(|_| "Invalid count")?,
+                    None => 1,
+                };
+                Ok(self.run(Some(count)))
+            }
+            "continue" | "c" => Ok(self.run(None)),
+            "back" => {
+                let count = match arguments.first() {
+                    Some(x) => x.parse::<usize>().map_err(|_| "Invalid count")?,
+                    None => 1,
+                };
+                let undone = self.computer.step_back(count);
+                self.drop_undone_outputs();
+                Ok(format!(
+                    "Undid {} instructions\n{}",
+                    undone,
+                    self.describe_current()
+                ))
+            }
+            "rewind" => {
+                let address = parse_address(arguments.first().copied())?;
+                let reached = self.computer.run_back_to(address);
+                self.drop_undone_outputs();
+                if !reached {
+                    return Err(format!(
+                        "{} was not executed within the recorded history\n{}",
+                        address,
+                        self.describe_current()
+                    ));
+                }
+                Ok(self.describe_current())
+            }
+            "who" => {
+                let address = parse_address(arguments.first().copied())?;
+                match self.computer.last_writer(address) {
+                    Some(write) => Ok(format!(
+                        "[{}] changed from {} to {} at step {} by\n{}",
+                        address,
+                        write.old_value,
+                        write.new_value,
+                        write.step,
+                        self.list(write.ip, 1)
+                    )),
+                    None => Ok(format!("No recorded writes to {}", address)),
+                }
+            }
+            "break" | "b" => {
+                let target = arguments.first().ok_or("Missing breakpoint")?;
+                if let Ok(address) = target.parse::<usize>() {
+                    self.breakpoints.insert(address);
+                    Ok(format!("Breakpoint at {}", address))
+                } else {
+                    let opcode =
+                        opcode_of(target).ok_or(format!("Unknown mnemonic '{}'", target))?;
+                    self.opcode_breakpoints.insert(opcode);
+                    Ok(format!("Breakpoint on every {}", target.to_uppercase()))
+                }
+            }
+            "watch" | "w" => {
+                let address = parse_address(arguments.first().copied())?;
+                self.watchpoints.insert(address);
+                Ok(format!("Watching writes to {}", address))
+            }
+            "clear" => {
+                self.breakpoints.clear();
+                self.opcode_breakpoints.clear();
+                self.watchpoints.clear();
+                Ok("Cleared all breakpoints and watchpoints".to_string())
+            }
+            "regs" | "r" => Ok(format!(
+                "ip={} relative_base={} input={}+{} state={}",
+                self.computer.current_op,
+                self.computer.relative_base,
+                self.computer.inputs_consumed,
+                self.computer.input.len(),
+                self.describe_state()
+            )),
+            "dump" | "x" => {
+                let start = parse_address(arguments.first().copied())?;
+                let length = match arguments.get(1) {
+                    Some(x) => x.parse::<usize>().map_err(|_| "Invalid length")?,
+                    None => 8,
+                };
+                let end = start
+                    .checked_add(length)
+                    .ok_or("Range past the last address")?;
+                let values: Vec<String> = (start..end)
+                    .map(|x| self.computer.get_value(x).to_string())
+                    .collect();
+                Ok(format!("{:>5}: {}", start, values.join(", ")))
+            }
+            "poke" | "p" => {
+                let address = parse_address(arguments.first().copied())?;
+                let value = arguments
+                    .get(1)
+                    .and_then(|x| x.parse::<i64>().ok())
+                    .ok_or("Missing or invalid value")?;
+                self.computer
+                    .write_memory(address, value)
+                    .map_err(|_| "Memory limit exceeded")?;
+                // Undoing earlier instructions would not undo the poke
+                self.computer.clear_history();
+                Ok(format!("[{}] = {}, undo history cleared", address, value))
+            }
+            "input" | "i" => {
+                for argument in arguments.iter() {
+                    let value = argument
+                        .parse::<i64>()
+                        .map_err(|_| format!("Invalid input '{}'", argument))?;
+                    self.computer.add_input(value);
+                }
+                Ok(format!("Queued {} values", arguments.len()))
+            }
+            "output" | "o" => {
+                let values: Vec<String> = self.output.iter().map(|x| x.to_string()).collect();
+                Ok(values.join(", "))
+            }
+            "list" | "l" => {
+                let start = match arguments.first() {
+                    Some(x) => parse_address(Some(x))?,
+                    None => self.computer.current_op,
+                };
+                let count = match arguments.get(1) {
+                    Some(x) => x.parse::<usize>().map_err(|_| "Invalid count")?,
+                    None => 10,
+                };
+                Ok(self.list(start, count))
+            }
+            "save" => {
+                let path = arguments.first().ok_or("Missing path")?;
+                std::fs::write(path, self.computer.to_snapshot())
+                    .map_err(|error| format!("Could not write {}: {}", path, error))?;
+                Ok(format!("Saved snapshot to {}", path))
+            }
+            "help" | "h" => Ok(HELP.to_string()),
+            _ => Err(format!("Unknown command '{}', try 'help'", command)),
+        }
+    }
+
+    /// Reads commands from stdin until `quit` or end of input.
+    pub fn run_interactive(&mut self) {
+        let stdin = std::io::stdin();
+        let mut lines = stdin.lock().lines();
+        println!("{}", self.describe_current());
+        loop {
+            print!("(icdb) ");
+            std::io::stdout().flush().unwrap();
+            let line = match lines.next() {
+                Some(Ok(line)) => line,
+                _ => break,
+            };
+            let line = line.trim();
+            if line == "quit" || line == "q" {
+                break;
+            }
+            match self.execute_command(line) {
+                Ok(text) if text.is_empty() => {}
+                Ok(text) => println!("{}", text),
+                Err(error) => println!("Error: {}", error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::debugger::Debugger;
+    use crate::intcode::IntCodeComputer;
+
+    fn debugger(program: &str) -> Debugger {
+        Debugger::new(IntCodeComputer::read_program(program))
+    }
+
+    #[test]
+    fn test_step_and_registers() {
+        let mut d = debugger("109,5,1101,1,2,20,99");
+        let report = d.execute_command("step 1").unwrap();
+        assert!(report.ends_with("    2: ADD #1, #2, [20]"), "{}", report);
+        assert_eq!(
+            d.execute_command("regs").unwrap(),
+            "ip=2 relative_base=5 input=0+0 state=running"
+        );
+        d.execute_command("step 2").unwrap();
+        assert_eq!(d.execute_command("dump 20 1").unwrap(), "   20: 3");
+        assert!(d.execute_command("regs").unwrap().ends_with("halted"));
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut d = debugger("3,0,4,0,3,0,4,0,99");
+        d.execute_command("break OUT").unwrap();
+        d.execute_command("input 7 8").unwrap();
+        let report = d.execute_command("continue").unwrap();
+        assert!(report.contains("(breakpoint)"), "{}", report);
+        assert!(report.ends_with("OUT [0]"));
+        let report = d.execute_command("continue").unwrap();
+        assert!(report.starts_with("Output: 7\n"), "{}", report);
+        d.execute_command("clear").unwrap();
+        d.execute_command("break 8").unwrap();
+        let report = d.execute_command("c").unwrap();
+        assert!(report.ends_with("    8: HLT"), "{}", report);
+        assert_eq!(d.execute_command("output").unwrap(), "7, 8");
+    }
+
+    #[test]
+    fn test_watchpoint_and_poke() {
+        let mut d = debugger("1101,1,1,10,1101,2,2,11,99,0,0,0");
+        d.execute_command("watch 11").unwrap();
+        let report = d.execute_command("c").unwrap();
+        assert!(report.contains("watchpoint: [11] = 4"), "{}", report);
+        d.execute_command("poke 11 42").unwrap();
+        assert_eq!(d.execute_command("dump 10 2").unwrap(), "   10: 2, 42");
+        assert!(d.execute_command("back").unwrap().starts_with("Undid 0 "));
+        assert_eq!(d.execute_command("dump 10 2").unwrap(), "   10: 2, 42");
+    }
+
+    #[test]
+    fn test_end_of_address_space() {
+        let mut d = debugger("99");
+        let last = usize::MAX.to_string();
+        assert!(d.execute_command(&format!("dump {} 8", last)).is_err());
+        assert_eq!(
+            d.execute_command(&format!("dump {} 1", usize::MAX - 1))
+                .unwrap(),
+            format!("{}: 0", usize::MAX - 1)
+        );
+        let listing = d.execute_command(&format!("list {} 3", last)).unwrap();
+        assert_eq!(listing, format!("{}: DATA 0", last));
+    }
+
+    #[test]
+    fn test_reverse_commands() {
+        let mut d = debugger("3,0,1001,0,5,0,4,0,99");
+        d.execute_command("input 7").unwrap();
+        d.execute_command("c").unwrap();
+        assert_eq!(d.execute_command("output").unwrap(), "12");
+        let report = d.execute_command("who 0").unwrap();
+        assert!(
+            report.starts_with("[0] changed from 7 to 12 at step 1 by\n    2: ADD [0], #5, [0]"),
+            "{}",
+            report
+        );
+        let report = d.execute_command("rewind 0").unwrap();
+        assert_eq!(report, "    0: IN [0]");
+        assert_eq!(d.execute_command("output").unwrap(), "");
+        assert_eq!(d.execute_command("dump 0 1").unwrap(), "    0: 3");
+        let report = d.execute_command("c").unwrap();
+        assert!(report.starts_with("Output: 12\n"), "{}", report);
+        assert_eq!(d.execute_command("output").unwrap(), "12");
+        assert!(d
+            .execute_command("back 2")
+            .unwrap()
+            .ends_with("    6: OUT [0]"));
+        assert_eq!(d.execute_command("output").unwrap(), "");
+        d.execute_command("c").unwrap();
+        assert_eq!(d.execute_command("output").unwrap(), "12");
+    }
+
+    #[test]
+    fn test_waiting_for_input() {
+        let mut d = debugger("3,0,99");
+        let report = d.execute_command("c").unwrap();
+        assert!(report.contains("waiting for input"), "{}", report);
+        assert!(d.execute_command("bogus").is_err());
+    }
+}
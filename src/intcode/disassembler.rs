@@ -0,0 +1,224 @@
+use crate::intcode::Op;
+use crate::intcode::Parameter;
+use std::collections::BTreeMap;
+
+const DATA_WORDS_PER_LINE: usize = 8;
+
+pub struct Entry {
+    pub address: usize,
+    pub words: Vec<i64>,
+    pub op: Option<Op>,
+}
+
+impl Entry {
+    pub fn render(&self) -> String {
+        let raw = self
+            .words
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let text = match &self.op {
+            Some(op) => op.to_string(),
+            None => format!(
+                "DATA {}",
+                self.words
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        format!("{:>5}: {:<24} {}", self.address, raw, text)
+    }
+}
+
+pub struct Listing {
+    pub entries: Vec<Entry>,
+}
+
+fn jump_target(parameter: &Parameter) -> Option<usize> {
+    match parameter {
+        Parameter::Immediate(x) => usize::try_from(*x).ok(),
+        _ => None,
+    }
+}
+
+pub fn is_unconditional_jump(op: &Op) -> bool {
+    match op {
+        Op::JumpIfTrue(Parameter::Immediate(x), _) => *x != 0,
+        Op::JumpIfFalse(Parameter::Immediate(x), _) => *x == 0,
+        _ => false,
+    }
+}
+
+/// Recognises the calling convention used by the puzzle programs: a constant
+/// return address is pushed onto the relative stack, followed by an
+/// unconditional jump. The return address is where execution resumes.
+pub fn return_address(memory: &[i64], address: usize, op: &Op) -> Option<usize> {
+    let value = match op {
+        Op::Add(Parameter::Immediate(a), Parameter::Immediate(b), Parameter::Relative(_)) => {
+            a.checked_add(*b)?
+        }
+        Op::Multiply(Parameter::Immediate(a), Parameter::Immediate(b), Parameter::Relative(_)) => {
+            a.checked_mul(*b)?
+        }
+        _ => return None,
+    };
+    let next_address = address + op.size();
+    let next = Op::decode(memory, next_address).ok()?;
+    if is_unconditional_jump(&next) && usize::try_from(value).ok()? == next_address + next.size() {
+        Some(next_address + next.size())
+    } else {
+        None
+    }
+}
+
+fn successors(memory: &[i64], address: usize, op: &Op) -> Vec<usize> {
+    let next_address = address + op.size();
+    let mut result = Vec::new();
+    match op {
+        Op::Halt => {}
+        Op::JumpIfTrue(_, target) | Op::JumpIfFalse(_, target) => {
+            if !is_unconditional_jump(op) {
+                result.push(next_address);
+            }
+            result.extend(jump_target(target));
+        }
+        _ => {
+            result.push(next_address);
+            result.extend(return_address(memory, address, op));
+        }
+    }
+    result
+}
+
+/// Finds the instructions reachable from address 0 and the given entry points
+/// by following fall-through and constant jump targets. Words with redundant
+/// mode digits are left as data so that the listing can be assembled back
+/// into the same program.
+fn find_code(memory: &[i64], entry_points: &[usize]) -> BTreeMap<usize, Op> {
+    let mut code = BTreeMap::new();
+    let mut claimed = vec![false; memory.len()];
+    let mut pending = entry_points.to_vec();
+    pending.push(0);
+    while let Some(address) = pending.pop() {
+        if address >= memory.len() || claimed[address] {
+            continue;
+        }
+        let op = match Op::decode(memory, address) {
+            Ok(op) => op,
+            Err(_) => continue,
+        };
+        let end = address + op.size();
+        if end > memory.len()
+            || claimed[address..end].iter().any(|x| *x)
+            || op.encode()[0] != memory[address]
+        {
+            continue;
+        }
+        claimed[address..end].fill(true);
+        pending.extend(successors(memory, address, &op));
+        code.insert(address, op);
+    }
+
+    code
+}
+
+impl Listing {
+    pub fn new(memory: &[i64]) -> Listing {
+        Listing::with_entry_points(memory, &[])
+    }
+
+    /// Like `new`, but also disassembles from addresses known to hold code,
+    /// such as ones reached through computed jumps.
+    pub fn with_entry_points(memory: &[i64], entry_points: &[usize]) -> Listing {
+        let mut code = find_code(memory, entry_points);
+        let mut entries = Vec::new();
+        let mut address = 0;
+        while address < memory.len() {
+            if let Some(op) = code.remove(&address) {
+                let size = op.size();
+                entries.push(Entry {
+                    address,
+                    words: memory[address..address + size].to_vec(),
+                    op: Some(op),
+                });
+                address += size;
+            } else {
+                let mut end = address + 1;
+                while end < memory.len()
+                    && end - address < DATA_WORDS_PER_LINE
+                    && !code.contains_key(&end)
+                {
+                    end += 1;
+                }
+                entries.push(Entry {
+                    address,
+                    words: memory[address..end].to_vec(),
+                    op: None,
+                });
+                address = end;
+            }
+        }
+
+        Listing { entries }
+    }
+
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        for entry in self.entries.iter() {
+            output.push_str(&entry.render());
+            output.push('\n');
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::disassembler::Listing;
+
+    #[test]
+    fn test_operand_rendering() {
+        let listing = Listing::new(&[21101, 5, -3, 7, 204, -1, 99]);
+        assert_eq!(
+            listing.render(),
+            "    0: 21101,5,-3,7             ADD #5, #-3, rb+7\n    4: 204,-1                   OUT rb-1\n    6: 99                       HLT\n"
+        );
+    }
+
+    #[test]
+    fn test_data_after_halt() {
+        let listing = Listing::new(&[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]);
+        let lines: Vec<String> = listing.entries.iter().map(|x| x.render()).collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].ends_with("ADD [9], [10], [3]"));
+        assert!(lines[1].ends_with("MUL [3], [11], [0]"));
+        assert!(lines[2].ends_with("HLT"));
+        assert!(lines[3].ends_with("DATA 30, 40, 50"));
+    }
+
+    #[test]
+    fn test_follows_jumps_and_calls() {
+        // Calls the subroutine at 12 with return address 9, then halts.
+        // Address 10 holds an invalid opcode that is never reached.
+        let listing = Listing::new(&[109, 20, 21101, 9, 0, 0, 1105, 1, 12, 99, 42, 0, 2106, 0, 0]);
+        let addresses: Vec<(usize, bool)> = listing
+            .entries
+            .iter()
+            .map(|x| (x.address, x.op.is_some()))
+            .collect();
+        assert_eq!(
+            addresses,
+            vec![
+                (0, true),
+                (2, true),
+                (6, true),
+                (9, true),
+                (10, false),
+                (12, true)
+            ]
+        );
+    }
+}
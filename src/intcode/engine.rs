@@ -0,0 +1,216 @@
+use crate::intcode::memory::Memory;
+use crate::intcode::Access;
+use crate::intcode::IntCodeComputer;
+use crate::intcode::IntcodeError;
+use crate::intcode::Op;
+use crate::intcode::Parameter;
+
+/// Largest number of words an instruction can take.
+const MAX_INSTRUCTION_SIZE: usize = 4;
+/// Instructions at higher addresses are decoded every time instead of cached.
+const CACHED_RANGE: usize = 1 << 20;
+/// Instructions per page of the cache.
+const PAGE_SIZE: usize = 1024;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Engine {
+    /// Decodes every instruction from memory each time it is executed.
+    Interpreter,
+    /// Decodes each instruction once and reuses it until its words are overwritten.
+    Cached,
+}
+
+impl Engine {
+    pub fn from_name(name: &str) -> Option<Engine> {
+        match name {
+            "interpreter" => Some(Engine::Interpreter),
+            "cached" => Some(Engine::Cached),
+            _ => None,
+        }
+    }
+}
+
+/// A parameter with its mode resolved when the instruction is decoded, so
+/// that running it again needs neither the mode nor the sign of a position.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operand {
+    Value(i64),
+    Address(usize),
+    Relative(i64),
+    /// A negative position, which faults only once the instruction uses it.
+    Negative(i64),
+}
+
+impl From<Parameter> for Operand {
+    fn from(parameter: Parameter) -> Operand {
+        match parameter {
+            Parameter::Immediate(x) => Operand::Value(x),
+            Parameter::Position(x) => match usize::try_from(x) {
+                Ok(address) => Operand::Address(address),
+                Err(_) => Operand::Negative(x),
+            },
+            Parameter::Relative(x) => Operand::Relative(x),
+        }
+    }
+}
+
+impl Access for Operand {
+    fn read(self, m: &IntCodeComputer) -> Result<i64, IntcodeError> {
+        match self {
+            Operand::Value(x) => Ok(x),
+            _ => Ok(m.memory.read(self.address(m)?)),
+        }
+    }
+
+    fn address(self, m: &IntCodeComputer) -> Result<usize, IntcodeError> {
+        match self {
+            Operand::Address(address) => Ok(address),
+            Operand::Relative(x) => {
+                let address = x.checked_add(m.relative_base).ok_or_else(|| m.overflow())?;
+                m.to_address(address)
+            }
+            Operand::Negative(x) => Err(m.negative_address(x)),
+            Operand::Value(_) => Err(IntcodeError::WriteToImmediate {
+                ip: m.current_op,
+                instruction: m.current_instruction(),
+            }),
+        }
+    }
+}
+
+/// The decoded instructions starting at each address of one page.
+type Page = Box<[Option<Op<Operand>>]>;
+
+/// Decoded instructions by the address they start at. Pages are allocated
+/// when an instruction on them is first cached, so code at a high address
+/// costs one page rather than an entry for every address below it.
+#[derive(Debug, Clone, Default)]
+pub struct DecodeCache {
+    pages: Vec<Option<Page>>,
+}
+
+/// The cache only holds what can be recomputed from memory, so two machines
+/// compare equal regardless of their cache contents.
+impl PartialEq for DecodeCache {
+    fn eq(&self, _: &DecodeCache) -> bool {
+        true
+    }
+}
+
+impl DecodeCache {
+    pub fn decode<M: Memory + ?Sized>(
+        &mut self,
+        memory: &M,
+        ip: usize,
+    ) -> Result<Op<Operand>, IntcodeError> {
+        if let Some(Some(page)) = self.pages.get(ip / PAGE_SIZE) {
+            if let Some(op) = page[ip % PAGE_SIZE] {
+                return Ok(op);
+            }
+        }
+        let op = Op::decode(memory, ip)?.map(Operand::from);
+        if ip >= CACHED_RANGE {
+            return Ok(op);
+        }
+        let index = ip / PAGE_SIZE;
+        if index >= self.pages.len() {
+            self.pages.resize(index + 1, None);
+        }
+        let page = self.pages[index].get_or_insert_with(|| vec![None; PAGE_SIZE].into());
+        page[ip % PAGE_SIZE] = Some(op);
+        Ok(op)
+    }
+
+    /// Forgets every instruction that covers `address`.
+    pub fn invalidate(&mut self, address: usize) {
+        let start = address.saturating_sub(MAX_INSTRUCTION_SIZE - 1);
+        for address in start..=address {
+            if let Some(Some(page)) = self.pages.get_mut(address / PAGE_SIZE) {
+                page[address % PAGE_SIZE] = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input_files::read_content;
+    use crate::intcode::engine::DecodeCache;
+    use crate::intcode::engine::Engine;
+    use crate::intcode::engine::Operand;
+    use crate::intcode::memory::Memory;
+    use crate::intcode::memory::PagedMemory;
+    use crate::intcode::IntCodeComputer;
+    use crate::intcode::Op;
+    use crate::intcode::Parameter;
+
+    fn run(program: &str, engine: Engine, input: &[i64]) -> Vec<i64> {
+        let mut m = IntCodeComputer::read_program(program);
+        m.set_engine(engine);
+        for value in input {
+            m.add_input(*value);
+        }
+        m.execute_until_halted().unwrap();
+        m.take_output()
+    }
+
+    #[test]
+    fn test_self_modifying_code() {
+        // The first pass through the loop rewrites the OUT [20] at 0 into OUT #20,
+        // so a stale cache entry would print 7 twice.
+        let program =
+            "4,20,1101,104,0,0,1001,21,1,21,1008,21,2,22,1006,22,0,99,0,0,7,0,0".to_string();
+        assert_eq!(run(&program, Engine::Interpreter, &[]), vec![7, 20]);
+        assert_eq!(run(&program, Engine::Cached, &[]), vec![7, 20]);
+    }
+
+    #[test]
+    fn test_engines_agree_on_puzzles() {
+        for (day, input) in [("05", 5), ("09", 1)] {
+            let content = read_content(&format!("data/day{}.txt", day)).unwrap();
+            assert_eq!(
+                run(&content, Engine::Cached, &[input]),
+                run(&content, Engine::Interpreter, &[input]),
+                "day {}",
+                day
+            );
+        }
+    }
+
+    #[test]
+    fn test_cache_at_high_address() {
+        let mut memory = PagedMemory::default();
+        memory.write(900_000, 99).unwrap();
+        let mut cache = DecodeCache::default();
+        cache.decode(&memory, 900_000).unwrap();
+        assert_eq!(cache.pages.iter().flatten().count(), 1);
+        cache.invalidate(900_002);
+        cache.invalidate(2_000_000);
+        assert_eq!(
+            cache.pages[900_000 / 1024].as_ref().unwrap()[900_000 % 1024],
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolved_operands() {
+        let mut memory = PagedMemory::default();
+        for (address, value) in [21101, 5, -3, 7].into_iter().enumerate() {
+            memory.write(address, value).unwrap();
+        }
+        let mut cache = DecodeCache::default();
+        assert_eq!(
+            cache.decode(&memory, 0),
+            Ok(Op::Add(
+                Operand::Value(5),
+                Operand::Value(-3),
+                Operand::Relative(7)
+            ))
+        );
+        assert_eq!(
+            Operand::from(Parameter::Position(-1)),
+            Operand::Negative(-1)
+        );
+        assert_eq!(Operand::from(Parameter::Position(12)), Operand::Address(12));
+    }
+}
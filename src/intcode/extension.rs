@@ -0,0 +1,275 @@
+use crate::intcode::IntCodeComputer;
+use crate::intcode::IntcodeError;
+use crate::intcode::Parameter;
+use crate::intcode::State;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Parameter modes are digits of the instruction, which limits how many an
+/// instruction can have.
+const MAX_PARAMETERS: usize = 8;
+/// Opcodes used by the instruction set itself.
+const BUILTIN_OPCODES: [i64; 10] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 99];
+
+/// What the machine does after an extension instruction.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Flow {
+    Continue,
+    /// Stop the current run after the instruction, for example to let another
+    /// machine run. Running again continues with the next instruction.
+    Yield,
+    /// Continue at the given address instead of the next instruction.
+    Jump(usize),
+}
+
+/// Receives the machine, positioned at the instruction, and its decoded parameters.
+pub type Handler =
+    dyn Fn(&mut IntCodeComputer, &[Parameter]) -> Result<Flow, IntcodeError> + Send + Sync;
+
+#[derive(Clone)]
+struct Extension {
+    parameters: usize,
+    handler: Arc<Handler>,
+}
+
+/// Instructions registered on a machine, by opcode. Cloned machines share
+/// them, and they are ignored when machines are compared.
+#[derive(Clone, Default)]
+pub struct Extensions(HashMap<i64, Extension>);
+
+impl Extensions {
+    pub fn handles(&self, instruction: i64) -> bool {
+        self.0.contains_key(&(instruction % 100))
+    }
+}
+
+impl PartialEq for Extensions {
+    fn eq(&self, _: &Extensions) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut opcodes: Vec<&i64> = self.0.keys().collect();
+        opcodes.sort();
+        write!(f, "Extensions({:?})", opcodes)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RegisterError {
+    /// The opcode is part of the instruction set or already registered.
+    Taken(i64),
+    /// Opcodes are the last two digits of an instruction.
+    OutOfRange(i64),
+    TooManyParameters(usize),
+}
+
+impl fmt::Display for RegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegisterError::Taken(opcode) => write!(f, "opcode {} is already in use", opcode),
+            RegisterError::OutOfRange(opcode) => {
+                write!(f, "opcode {} is not between 1 and 99", opcode)
+            }
+            RegisterError::TooManyParameters(count) => write!(
+                f,
+                "{} parameters requested, at most {} are supported",
+                count, MAX_PARAMETERS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RegisterError {}
+
+impl IntCodeComputer {
+    /// Adds an instruction with `parameters` parameters under an unused
+    /// opcode. Extension instructions are not traced, and memory they write
+    /// is not restored when stepping back.
+    pub fn register_opcode(
+        &mut self,
+        opcode: i64,
+        parameters: usize,
+        handler: impl Fn(&mut IntCodeComputer, &[Parameter]) -> Result<Flow, IntcodeError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Result<(), RegisterError> {
+        if !(1..=99).contains(&opcode) {
+            return Err(RegisterError::OutOfRange(opcode));
+        }
+        if BUILTIN_OPCODES.contains(&opcode) || self.extensions.0.contains_key(&opcode) {
+            return Err(RegisterError::Taken(opcode));
+        }
+        if parameters > MAX_PARAMETERS {
+            return Err(RegisterError::TooManyParameters(parameters));
+        }
+        let extension = Extension {
+            parameters,
+            handler: Arc::new(handler),
+        };
+        self.extensions.0.insert(opcode, extension);
+        Ok(())
+    }
+
+    /// The error for an extension instruction to return to fault the machine.
+    pub fn trap(&self) -> IntcodeError {
+        IntcodeError::Trap {
+            ip: self.current_op,
+            instruction: self.current_instruction(),
+        }
+    }
+
+    pub(super) fn execute_extension(&mut self) -> Result<(), IntcodeError> {
+        let instruction = self.current_instruction();
+        let extension = self.extensions.0[&(instruction % 100)].clone();
+        let mut parameters = Vec::new();
+        for index in 1..=extension.parameters {
+            let mode = (instruction / 10_i64.pow(index as u32 + 1)) % 10;
+            let value = self.get_value(self.current_op + index);
+            parameters.push(
+                Parameter::from(mode, value).ok_or(IntcodeError::InvalidMode {
+                    ip: self.current_op,
+                    instruction,
+                    mode,
+                })?,
+            );
+        }
+        match (extension.handler)(self, &parameters)? {
+            Flow::Continue => self.current_op += extension.parameters + 1,
+            Flow::Yield => {
+                self.current_op += extension.parameters + 1;
+                self.state = State::Yielded;
+            }
+            Flow::Jump(address) => self.current_op = address,
+        }
+        Ok(())
+    }
+}
+
+/// Ready-made extension instructions.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Builtin {
+    /// Prints its parameter to stderr.
+    Print,
+    /// Faults the machine.
+    Trap,
+    Yield,
+    /// Continues at the address its parameter gives.
+    Jump,
+}
+
+impl Builtin {
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        match name {
+            "print" => Some(Builtin::Print),
+            "trap" => Some(Builtin::Trap),
+            "yield" => Some(Builtin::Yield),
+            "jump" => Some(Builtin::Jump),
+            _ => None,
+        }
+    }
+
+    pub fn register(self, m: &mut IntCodeComputer, opcode: i64) -> Result<(), RegisterError> {
+        match self {
+            Builtin::Print => m.register_opcode(opcode, 1, |m, parameters| {
+                eprintln!("debug: {}", m.read_parameter(&parameters[0])?);
+                Ok(Flow::Continue)
+            }),
+            Builtin::Trap => m.register_opcode(opcode, 0, |m, _| Err(m.trap())),
+            Builtin::Yield => m.register_opcode(opcode, 0, |_, _| Ok(Flow::Yield)),
+            Builtin::Jump => m.register_opcode(opcode, 1, |m, parameters| {
+                let target = m.read_parameter(&parameters[0])?;
+                Ok(Flow::Jump(m.to_address(target)?))
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::extension::Builtin;
+    use crate::intcode::extension::Flow;
+    use crate::intcode::extension::RegisterError;
+    use crate::intcode::IntCodeComputer;
+    use crate::intcode::IntcodeError;
+    use crate::intcode::Outcome;
+
+    #[test]
+    fn test_custom_instruction() {
+        // 42 a b c stores max(a, b) in c
+        let mut m = IntCodeComputer::read_program("1142,7,1108,9,99,0,0,0,0,0");
+        m.register_opcode(42, 3, |m, parameters| {
+            let value = m
+                .read_parameter(&parameters[0])?
+                .max(m.read_parameter(&parameters[1])?);
+            m.write_parameter(&parameters[2], value)?;
+            Ok(Flow::Continue)
+        })
+        .unwrap();
+        m.execute_until_stopped().unwrap();
+        assert_eq!(m.get_value(9), 1108);
+        assert!(m.has_terminated());
+    }
+
+    #[test]
+    fn test_registration_errors() {
+        let mut m = IntCodeComputer::read_program("99");
+        assert_eq!(
+            Builtin::Trap.register(&mut m, 4),
+            Err(RegisterError::Taken(4))
+        );
+        assert_eq!(
+            Builtin::Trap.register(&mut m, 100),
+            Err(RegisterError::OutOfRange(100))
+        );
+        assert_eq!(
+            m.register_opcode(50, 9, |_, _| Ok(Flow::Continue)),
+            Err(RegisterError::TooManyParameters(9))
+        );
+        Builtin::Trap.register(&mut m, 50).unwrap();
+        assert_eq!(
+            Builtin::Yield.register(&mut m, 50),
+            Err(RegisterError::Taken(50))
+        );
+    }
+
+    #[test]
+    fn test_trap_and_yield() {
+        let mut m = IntCodeComputer::read_program("104,1,44,104,2,43,99");
+        Builtin::Trap.register(&mut m, 43).unwrap();
+        Builtin::Yield.register(&mut m, 44).unwrap();
+        assert_eq!(
+            m.execute_until_stopped_within(Default::default()),
+            Ok(Outcome::Yielded)
+        );
+        assert_eq!(m.take_output(), vec![1]);
+        assert_eq!(
+            m.execute_until_stopped(),
+            Err(IntcodeError::Trap {
+                ip: 5,
+                instruction: 43
+            })
+        );
+        assert_eq!(m.take_output(), vec![2]);
+    }
+
+    #[test]
+    fn test_jump() {
+        // Jumps over the first output, then to a negative address
+        let mut m = IntCodeComputer::read_program("145,4,104,1,104,2,145,-1");
+        Builtin::Jump.register(&mut m, 45).unwrap();
+        assert_eq!(
+            m.execute_until_stopped(),
+            Err(IntcodeError::NegativeAddress {
+                ip: 6,
+                instruction: 145,
+                address: -1
+            })
+        );
+        assert_eq!(m.take_output(), vec![2]);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::intcode::engine::Engine;
+use crate::intcode::memory::PagedMemory;
+use crate::intcode::Budget;
+use crate::intcode::IntCodeComputer;
+use crate::intcode::IntcodeError;
+use crate::intcode::Op;
+use crate::intcode::Outcome;
+use crate::intcode::Parameter;
+use std::fmt;
+use std::panic::AssertUnwindSafe;
+
+/// Instructions each generated program may execute.
+const CYCLE_BUDGET: u64 = 10_000;
+/// Keeps programs that write all over memory cheap to run.
+const MEMORY_LIMIT: usize = 1 << 16;
+const MAX_PROGRAM_SIZE: usize = 64;
+const MAX_INPUTS: usize = 4;
+/// Every engine is compared against the interpreter.
+const ALTERNATIVE_ENGINES: [Engine; 1] = [Engine::Cached];
+
+/// A small xorshift generator, so that runs can be repeated from a seed.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // Zero is a fixed point of xorshift
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    /// Mostly small numbers that make sense as addresses and offsets, with the
+    /// occasional extreme value.
+    fn word(&mut self) -> i64 {
+        match self.below(10) {
+            0 => [i64::MIN, i64::MAX, -1, 1 << 40][self.below(4)],
+            1 => self.next() as i64,
+            2 => -(self.below(MAX_PROGRAM_SIZE) as i64),
+            _ => self.below(MAX_PROGRAM_SIZE * 2) as i64,
+        }
+    }
+}
+
+fn parameter(rng: &mut Rng) -> Parameter {
+    let value = rng.word();
+    match rng.below(3) {
+        0 => Parameter::Position(value),
+        1 => Parameter::Immediate(value),
+        _ => Parameter::Relative(value),
+    }
+}
+
+fn instruction(rng: &mut Rng) -> Vec<i64> {
+    let kind = rng.below(10);
+    let mut p = || parameter(rng);
+    let op = match kind {
+        0 => Op::Add(p(), p(), p()),
+        1 => Op::Multiply(p(), p(), p()),
+        2 => Op::Input(p()),
+        3 => Op::Output(p()),
+        4 => Op::JumpIfTrue(p(), p()),
+        5 => Op::JumpIfFalse(p(), p()),
+        6 => Op::LessThan(p(), p(), p()),
+        7 => Op::Equals(p(), p(), p()),
+        8 => Op::AdjustRelativeBase(p()),
+        _ => Op::Halt,
+    };
+    op.encode()
+}
+
+/// A program made of well formed instructions, with some raw words mixed in.
+pub fn generate(rng: &mut Rng) -> Vec<i64> {
+    let size = 1 + rng.below(MAX_PROGRAM_SIZE);
+    let mut program = Vec::new();
+    while program.len() < size {
+        if rng.below(8) == 0 {
+            program.push(rng.word());
+        } else {
+            program.extend(instruction(rng));
+        }
+    }
+    program
+}
+
+/// Changes, inserts or removes a few words of `program`.
+pub fn mutate(rng: &mut Rng, program: &[i64]) -> Vec<i64> {
+    let mut program = program.to_vec();
+    for _ in 0..1 + rng.below(3) {
+        let index = rng.below(program.len() + 1);
+        match rng.below(4) {
+            0 if index < program.len() => {
+                program.remove(index);
+            }
+            1 => program.insert(index, rng.word()),
+            2 => {
+                let words = instruction(rng);
+                program.splice(index..index, words);
+            }
+            _ if index < program.len() => program[index] = rng.word(),
+            _ => program.push(rng.word()),
+        }
+    }
+    if program.is_empty() {
+        program.push(99);
+    }
+    program
+}
+
+/// Everything observable about a finished run.
+#[derive(Debug, PartialEq)]
+struct RunResult {
+    outcome: Result<Outcome, IntcodeError>,
+    output: Vec<i64>,
+    ip: usize,
+    relative_base: i64,
+    memory: PagedMemory,
+}
+
+fn run(program: &[i64], input: &[i64], engine: Engine) -> Result<RunResult, String> {
+    let text: Vec<String> = program.iter().map(|x| x.to_string()).collect();
+    std::panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut m = IntCodeComputer::read_program(&text.join(","));
+        m.set_engine(engine);
+        m.set_memory_limit(Some(MEMORY_LIMIT));
+        for value in input {
+            m.add_input(*value);
+        }
+        let outcome = m.execute_until_stopped_within(Budget::cycles(CYCLE_BUDGET));
+        RunResult {
+            outcome,
+            output: m.take_output(),
+            ip: m.current_op,
+            relative_base: m.relative_base,
+            memory: m.memory,
+        }
+    }))
+    .map_err(|payload| {
+        payload
+            .downcast_ref::<&str>()
+            .map(|x| x.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default()
+    })
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Problem {
+    Panicked { engine: Engine, message: String },
+    Disagreed { engine: Engine },
+}
+
+impl Problem {
+    /// Whether both problems are the same kind of failure in the same engine,
+    /// which is what shrinking has to preserve.
+    fn same_kind(&self, other: &Problem) -> bool {
+        match (self, other) {
+            (Problem::Panicked { engine: a, .. }, Problem::Panicked { engine: b, .. }) => a == b,
+            (Problem::Disagreed { engine: a }, Problem::Disagreed { engine: b }) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Panicked { engine, message } => {
+                write!(f, "{:?} engine panicked: {}", engine, message)
+            }
+            Problem::Disagreed { engine } => {
+                write!(f, "{:?} engine disagrees with the interpreter", engine)
+            }
+        }
+    }
+}
+
+/// Runs a program under every engine and checks that none of them panic and
+/// that they all end up in the same state.
+pub fn check(program: &[i64], input: &[i64]) -> Result<(), Problem> {
+    let expected =
+        run(program, input, Engine::Interpreter).map_err(|message| Problem::Panicked {
+            engine: Engine::Interpreter,
+            message,
+        })?;
+    for engine in ALTERNATIVE_ENGINES {
+        let actual =
+            run(program, input, engine).map_err(|message| Problem::Panicked { engine, message })?;
+        if actual != expected {
+            return Err(Problem::Disagreed { engine });
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Failure {
+    pub program: Vec<i64>,
+    pub input: Vec<i64>,
+    pub problem: Problem,
+}
+
+impl Failure {
+    /// Repeatedly removes words and makes them smaller for as long as the
+    /// same kind of problem still occurs.
+    pub fn shrink(self) -> Failure {
+        let mut best = self;
+        let fails = |program: &[i64], input: &[i64], best: &Failure| match check(program, input) {
+            Err(problem) if problem.same_kind(&best.problem) => Some(problem),
+            _ => None,
+        };
+        let mut progress = true;
+        while progress {
+            progress = false;
+            for index in (0..best.program.len()).rev() {
+                let mut candidates = Vec::new();
+                if best.program.len() > 1 {
+                    let mut removed = best.program.clone();
+                    removed.remove(index);
+                    candidates.push(removed);
+                }
+                let value = best.program[index];
+                for smaller in [0, value / 2] {
+                    if smaller != value {
+                        let mut replaced = best.program.clone();
+                        replaced[index] = smaller;
+                        candidates.push(replaced);
+                    }
+                }
+                for candidate in candidates {
+                    if let Some(problem) = fails(&candidate, &best.input, &best) {
+                        best = Failure {
+                            program: candidate,
+                            input: best.input.clone(),
+                            problem,
+                        };
+                        progress = true;
+                        break;
+                    }
+                }
+            }
+            if !best.input.is_empty() {
+                let input = &best.input[..best.input.len() - 1];
+                if let Some(problem) = fails(&best.program, input, &best) {
+                    best = Failure {
+                        program: best.program.clone(),
+                        input: input.to_vec(),
+                        problem,
+                    };
+                    progress = true;
+                }
+            }
+        }
+        best
+    }
+
+    /// A line for data/fuzz_regressions.txt that reproduces this failure,
+    /// after a comment describing it.
+    pub fn to_regression_case(&self) -> String {
+        let join = |values: &[i64]| {
+            values
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        format!(
+            "# {}\n{};{}\n",
+            self.problem,
+            join(&self.program),
+            join(&self.input)
+        )
+    }
+}
+
+/// Checks `iterations` programs, half of them generated from scratch and half
+/// mutated from earlier ones, and returns the first failure found, shrunk.
+pub fn fuzz(seed: u64, iterations: usize) -> Option<Failure> {
+    let mut rng = Rng::new(seed);
+    let mut corpus: Vec<Vec<i64>> = Vec::new();
+    for _ in 0..iterations {
+        let program = if corpus.is_empty() || rng.below(2) == 0 {
+            generate(&mut rng)
+        } else {
+            let parent = rng.below(corpus.len());
+            mutate(&mut rng, &corpus[parent])
+        };
+        let input: Vec<i64> = (0..rng.below(MAX_INPUTS + 1)).map(|_| rng.word()).collect();
+        if let Err(problem) = check(&program, &input) {
+            return Some(
+                Failure {
+                    program,
+                    input,
+                    problem,
+                }
+                .shrink(),
+            );
+        }
+        corpus.push(program);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::engine::Engine;
+    use crate::intcode::fuzz::fuzz;
+    use crate::intcode::fuzz::Failure;
+    use crate::intcode::fuzz::Problem;
+
+    #[test]
+    fn test_fuzz() {
+        assert_eq!(fuzz(1, 2000), None);
+    }
+
+    #[test]
+    fn test_regression_case_text() {
+        let failure = Failure {
+            program: vec![1101, 1, 2, 0, 99],
+            input: vec![],
+            problem: Problem::Disagreed {
+                engine: Engine::Cached,
+            },
+        };
+        assert_eq!(
+            failure.clone().shrink().program,
+            vec![1101, 1, 2, 0, 99],
+            "a passing program cannot be shrunk"
+        );
+        assert!(failure.to_regression_case().ends_with("\n1101,1,2,0,99;\n"));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::intcode::IntCodeComputer;
+use crate::intcode::Op;
+use crate::intcode::State;
+use std::collections::VecDeque;
+
+/// What one executed instruction changed, so that it can be undone.
+#[derive(Debug, PartialEq, Clone)]
+pub struct UndoEntry {
+    ip: usize,
+    relative_base: i64,
+    state: State,
+    last_output: Option<i64>,
+    /// Address written and the value it held before.
+    write: Option<(usize, i64)>,
+    /// Value taken from the input.
+    input: Option<i64>,
+    output: bool,
+}
+
+/// A bounded undo log. The oldest entries are dropped once it is full.
+#[derive(Debug, PartialEq, Clone)]
+pub struct History {
+    entries: VecDeque<UndoEntry>,
+    capacity: usize,
+    /// Instructions executed since recording started, including dropped entries.
+    steps: u64,
+    /// Undone outputs that had already been taken from the machine.
+    taken_outputs_undone: usize,
+}
+
+/// The most recent recorded write to an address.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LastWrite {
+    /// Number of instructions executed since recording started, before the write.
+    pub step: u64,
+    pub ip: usize,
+    pub old_value: i64,
+    pub new_value: i64,
+}
+
+impl IntCodeComputer {
+    /// Starts recording an undo log of up to `capacity` instructions.
+    pub fn enable_history(&mut self, capacity: usize) {
+        self.history = Some(History {
+            entries: VecDeque::new(),
+            capacity,
+            steps: 0,
+            taken_outputs_undone: 0,
+        });
+    }
+
+    /// Forgets every recorded instruction. Used when memory is changed from
+    /// outside the program, which the recorded instructions cannot undo.
+    pub fn clear_history(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            history.entries.clear();
+        }
+    }
+
+    /// Captures the state the next instruction may change.
+    pub(super) fn begin_undo(&self) -> UndoEntry {
+        let target = self.write_target();
+        UndoEntry {
+            ip: self.current_op,
+            relative_base: self.relative_base,
+            state: self.state.clone(),
+            last_output: self.last_output,
+            write: target.map(|address| (address, self.get_value(address))),
+            input: None,
+            output: matches!(self.interpret_op(), Ok(Op::Output(_))),
+        }
+    }
+
+    pub(super) fn finish_undo(&mut self, mut entry: UndoEntry, inputs_consumed: usize) {
+        if self.state == State::Waiting {
+            return;
+        }
+        let faulted = matches!(self.state, State::Faulted(_));
+        if faulted {
+            entry.write = None;
+            entry.output = false;
+        }
+        if self.inputs_consumed > inputs_consumed {
+            entry.input = entry.write.map(|(address, _)| self.get_value(address));
+        }
+        let history = self.history.as_mut().unwrap();
+        if history.entries.len() == history.capacity {
+            history.entries.pop_front();
+        }
+        history.entries.push_back(entry);
+        history.steps += 1;
+    }
+
+    fn undo(&mut self, entry: UndoEntry) {
+        if let Some((address, value)) = entry.write {
+            // Restoring a cell that was written before cannot allocate
+            self.write_memory(address, value).unwrap();
+        }
+        if let Some(value) = entry.input {
+            self.input.push_front(value);
+            self.inputs_consumed -= 1;
+        }
+        // Output already taken is counted so the taker can drop it
+        if entry.output && self.output.pop_back().is_none() {
+            self.history.as_mut().unwrap().taken_outputs_undone += 1;
+        }
+        self.current_op = entry.ip;
+        self.relative_base = entry.relative_base;
+        self.state = entry.state;
+        self.last_output = entry.last_output;
+    }
+
+    /// How many outputs of undone instructions had already been taken with
+    /// `take_output`, since this was last called. Whoever took them should
+    /// drop that many of the latest.
+    pub fn take_undone_outputs(&mut self) -> usize {
+        self.history
+            .as_mut()
+            .map_or(0, |x| std::mem::take(&mut x.taken_outputs_undone))
+    }
+
+    /// Undoes up to `count` instructions and returns how many were undone.
+    pub fn step_back(&mut self, count: usize) -> usize {
+        let mut undone = 0;
+        while undone < count {
+            let entry = match self.history.as_mut().and_then(|x| x.entries.pop_back()) {
+                Some(entry) => entry,
+                None => break,
+            };
+            self.history.as_mut().unwrap().steps -= 1;
+            self.undo(entry);
+            undone += 1;
+        }
+        undone
+    }
+
+    /// Undoes instructions until the machine is about to execute `address`
+    /// again. Returns false, having undone everything recorded, if it never was.
+    pub fn run_back_to(&mut self, address: usize) -> bool {
+        while self.step_back(1) == 1 {
+            if self.current_op == address {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// The latest recorded instruction that wrote to `address`.
+    pub fn last_writer(&self, address: usize) -> Option<LastWrite> {
+        let history = self.history.as_ref()?;
+        let first_step = history.steps - history.entries.len() as u64;
+        history
+            .entries
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, entry)| entry.write.is_some_and(|(target, _)| target == address))
+            .map(|(index, entry)| {
+                let (_, old_value) = entry.write.unwrap();
+                LastWrite {
+                    step: first_step + index as u64,
+                    ip: entry.ip,
+                    old_value,
+                    new_value: self.get_value(address),
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input_files::read_content;
+    use crate::intcode::history::LastWrite;
+    use crate::intcode::IntCodeComputer;
+
+    #[test]
+    fn test_step_back_restores_state() {
+        let content = read_content("data/day09.txt").unwrap();
+        let mut m = IntCodeComputer::read_program_with_input(&content, 2);
+        m.enable_history(1000);
+        for _ in 0..200 {
+            m.execute_step().unwrap();
+        }
+        let checkpoint = m.clone();
+        for _ in 0..300 {
+            m.execute_step().unwrap();
+        }
+        assert_eq!(m.step_back(300), 300);
+        assert_eq!(m, checkpoint);
+        assert_eq!(m.step_back(1000), 200);
+        let mut initial = IntCodeComputer::read_program_with_input(&content, 2);
+        initial.enable_history(1000);
+        assert_eq!(m, initial);
+        m.execute_until_halted().unwrap();
+        assert_eq!(m.take_output(), vec![81348]);
+    }
+
+    #[test]
+    fn test_run_back_and_last_writer() {
+        // Adds one to [14] until it reaches 3, then outputs it
+        let program = "1001,14,1,14,1008,14,3,15,1006,15,0,4,14,99,0,0".to_string();
+        let mut m = IntCodeComputer::read_program(&program);
+        m.enable_history(5);
+        m.execute_until_halted().unwrap();
+        assert_eq!(m.take_output(), vec![3]);
+        assert_eq!(
+            m.last_writer(14),
+            Some(LastWrite {
+                step: 6,
+                ip: 0,
+                old_value: 2,
+                new_value: 3
+            })
+        );
+        assert!(m.run_back_to(4));
+        assert!(m.run_back_to(0));
+        assert_eq!(m.get_value(14), 2);
+        // Only the last five instructions were recorded
+        assert!(!m.run_back_to(0));
+        assert_eq!(m.last_writer(14), None);
+    }
+}
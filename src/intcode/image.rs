@@ -0,0 +1,366 @@
+use crate::gzip;
+use crate::gzip::GzipError;
+use crate::intcode::IntCodeComputer;
+use std::fmt;
+
+const BINARY_MAGIC: &[u8; 4] = b"ICIM";
+const BINARY_VERSION: u8 = 1;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    /// Comma separated decimal words, as the puzzle inputs are given.
+    Text,
+    /// A header followed by every word as a zigzag encoded varint.
+    Binary,
+    /// Gzip compressed text.
+    Gzip,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "binary" => Some(Format::Binary),
+            "gzip" => Some(Format::Gzip),
+            _ => None,
+        }
+    }
+
+    /// The format a file name suggests: `.gz` for gzip, `.icb` for binary and
+    /// text for anything else.
+    pub fn from_path(path: &str) -> Format {
+        if path.ends_with(".gz") {
+            Format::Gzip
+        } else if path.ends_with(".icb") {
+            Format::Binary
+        } else {
+            Format::Text
+        }
+    }
+
+    pub fn detect(data: &[u8]) -> Format {
+        if gzip::is_gzip(data) {
+            Format::Gzip
+        } else if data.starts_with(BINARY_MAGIC) {
+            Format::Binary
+        } else {
+            Format::Text
+        }
+    }
+}
+
+/// A program or memory dump, and the address execution starts at.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Image {
+    pub words: Vec<i64>,
+    pub entry: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ImageError {
+    InvalidWord {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    NotUtf8,
+    InvalidBinary {
+        offset: usize,
+        reason: &'static str,
+    },
+    UnsupportedVersion(u8),
+    Gzip(GzipError),
+    /// Text has nowhere to record an entry point other than 0.
+    EntryNotStorable(usize),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::InvalidWord {
+                line,
+                column,
+                token,
+            } => write!(
+                f,
+                "invalid word '{}' at line {}, column {}",
+                token, line, column
+            ),
+            ImageError::NotUtf8 => write!(f, "text image is not valid UTF-8"),
+            ImageError::InvalidBinary { offset, reason } => {
+                write!(f, "invalid binary image at byte {}: {}", offset, reason)
+            }
+            ImageError::UnsupportedVersion(version) => {
+                write!(f, "unsupported binary image version {}", version)
+            }
+            ImageError::Gzip(error) => write!(f, "{}", error),
+            ImageError::EntryNotStorable(entry) => write!(
+                f,
+                "entry point {} can only be stored in the binary format",
+                entry
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<GzipError> for ImageError {
+    fn from(error: GzipError) -> ImageError {
+        ImageError::Gzip(error)
+    }
+}
+
+/// Parses comma separated words, reporting where the first bad one is.
+pub fn parse_text(text: &str) -> Result<Vec<i64>, ImageError> {
+    let mut words = Vec::new();
+    let mut offset = 0;
+    for token in text.split(',') {
+        let trimmed = token.trim();
+        match trimmed.parse::<i64>() {
+            Ok(value) => words.push(value),
+            Err(_) => {
+                let start = offset + token.len() - token.trim_start().len();
+                let before = &text[..start];
+                let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+                return Err(ImageError::InvalidWord {
+                    line: before.matches('\n').count() + 1,
+                    column: before[line_start..].chars().count() + 1,
+                    token: trimmed.to_string(),
+                });
+            }
+        }
+        offset += token.len() + 1;
+    }
+    Ok(words)
+}
+
+fn to_text(image: &Image) -> Result<String, ImageError> {
+    if image.entry != 0 {
+        return Err(ImageError::EntryNotStorable(image.entry));
+    }
+    let words: Vec<String> = image.words.iter().map(|x| x.to_string()).collect();
+    Ok(format!("{}\n", words.join(",")))
+}
+
+fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+/// Maps small negative and positive numbers alike to small unsigned ones.
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+fn to_binary(image: &Image) -> Vec<u8> {
+    let mut output = BINARY_MAGIC.to_vec();
+    output.push(BINARY_VERSION);
+    write_varint(&mut output, image.entry as u64);
+    write_varint(&mut output, image.words.len() as u64);
+    for word in image.words.iter() {
+        write_varint(&mut output, zigzag(*word));
+    }
+    output
+}
+
+struct BinaryReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl BinaryReader<'_> {
+    fn error(&self, reason: &'static str) -> ImageError {
+        ImageError::InvalidBinary {
+            offset: self.offset,
+            reason,
+        }
+    }
+
+    fn varint(&mut self) -> Result<u64, ImageError> {
+        let mut value = 0_u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .data
+                .get(self.offset)
+                .ok_or_else(|| self.error("unexpected end of data"))?;
+            if shift == 63 && byte > 1 {
+                return Err(self.error("varint too large"));
+            }
+            self.offset += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.error("varint too long"))
+    }
+}
+
+fn from_binary(data: &[u8]) -> Result<Image, ImageError> {
+    let mut reader = BinaryReader {
+        data,
+        offset: BINARY_MAGIC.len(),
+    };
+    let version = *data
+        .get(reader.offset)
+        .ok_or_else(|| reader.error("missing version"))?;
+    if version != BINARY_VERSION {
+        return Err(ImageError::UnsupportedVersion(version));
+    }
+    reader.offset += 1;
+    let entry = usize::try_from(reader.varint()?).map_err(|_| reader.error("entry too large"))?;
+    let count = reader.varint()?;
+    // Every word takes at least a byte, which bounds the allocation below
+    if count > (data.len() - reader.offset) as u64 {
+        return Err(reader.error("more words than data"));
+    }
+    let mut words = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        words.push(unzigzag(reader.varint()?));
+    }
+    if reader.offset != data.len() {
+        return Err(reader.error("trailing data"));
+    }
+    Ok(Image { words, entry })
+}
+
+/// Reads an image in any of the formats, detecting which one it is.
+pub fn load(data: &[u8]) -> Result<Image, ImageError> {
+    match Format::detect(data) {
+        Format::Text => {
+            let text = std::str::from_utf8(data).map_err(|_| ImageError::NotUtf8)?;
+            Ok(Image {
+                words: parse_text(text)?,
+                entry: 0,
+            })
+        }
+        Format::Binary => from_binary(data),
+        Format::Gzip => {
+            let data = gzip::decompress(data)?;
+            match Format::detect(&data) {
+                Format::Gzip => Err(ImageError::Gzip(GzipError::InvalidData(
+                    "nested gzip data".to_string(),
+                ))),
+                _ => load(&data),
+            }
+        }
+    }
+}
+
+pub fn save(image: &Image, format: Format) -> Result<Vec<u8>, ImageError> {
+    match format {
+        Format::Text => Ok(to_text(image)?.into_bytes()),
+        Format::Binary => Ok(to_binary(image)),
+        Format::Gzip => Ok(gzip::compress(to_text(image)?.as_bytes())),
+    }
+}
+
+impl IntCodeComputer {
+    /// A machine that starts at the image's entry point.
+    pub fn from_image(image: &Image) -> IntCodeComputer {
+        let mut m = IntCodeComputer::from_words(&image.words);
+        m.current_op = image.entry;
+        m
+    }
+
+    /// Dumps memory, with the current instruction as the entry point. Far
+    /// memory, registers other than the instruction pointer and pending
+    /// input are not included; snapshots keep those.
+    pub fn to_image(&self) -> Image {
+        Image {
+            words: self.get_memory(),
+            entry: self.current_op,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input_files::read_content;
+    use crate::intcode::image::load;
+    use crate::intcode::image::parse_text;
+    use crate::intcode::image::save;
+    use crate::intcode::image::Format;
+    use crate::intcode::image::Image;
+    use crate::intcode::image::ImageError;
+    use crate::intcode::IntCodeComputer;
+
+    #[test]
+    fn test_text_errors() {
+        assert_eq!(parse_text(" 1, -2,3\n").unwrap(), vec![1, -2, 3]);
+        assert_eq!(
+            parse_text("1,2,3,\n4,x5,6").unwrap_err(),
+            ImageError::InvalidWord {
+                line: 2,
+                column: 3,
+                token: "x5".to_string()
+            }
+        );
+        assert_eq!(
+            parse_text("1,,2").unwrap_err().to_string(),
+            "invalid word '' at line 1, column 3"
+        );
+    }
+
+    #[test]
+    fn test_formats_round_trip() {
+        let image = Image {
+            words: vec![0, 1, -1, 63, -64, 64, i64::MAX, i64::MIN, 1 << 40],
+            entry: 0,
+        };
+        for format in [Format::Text, Format::Binary, Format::Gzip] {
+            let data = save(&image, format).unwrap();
+            assert_eq!(Format::detect(&data), format);
+            assert_eq!(load(&data).unwrap(), image, "{:?}", format);
+        }
+        let binary = save(&image, Format::Binary).unwrap();
+        assert_eq!(&binary[..7], b"ICIM\x01\x00\x09");
+        assert_eq!(binary[7..12], [0, 2, 1, 126, 127]);
+        assert_eq!(
+            load(&binary[..binary.len() - 1]).unwrap_err(),
+            ImageError::InvalidBinary {
+                offset: binary.len() - 1,
+                reason: "unexpected end of data"
+            }
+        );
+    }
+
+    #[test]
+    fn test_gzip_archive() {
+        let compressed = std::fs::read("data/day09.txt.gz").unwrap();
+        let text = read_content("data/day09.txt").unwrap();
+        assert_eq!(load(&compressed).unwrap().words, parse_text(&text).unwrap());
+    }
+
+    #[test]
+    fn test_memory_dump_round_trip() {
+        // Doubles each input, keeping the last result in [9]
+        let mut m = IntCodeComputer::read_program("3,9,1002,9,2,9,4,9,1105,1,0");
+        m.add_input(21);
+        m.execute_until_stopped().unwrap();
+        let image = m.to_image();
+        assert_eq!(image.entry, 0);
+        assert_eq!(image.words[9], 42);
+        m.add_input(5);
+        m.run_until_output(2).unwrap();
+        let dump = m.to_image();
+        assert_eq!(dump.entry, 8);
+        assert_eq!(
+            save(&dump, Format::Text).unwrap_err(),
+            ImageError::EntryNotStorable(8)
+        );
+        let mut restored =
+            IntCodeComputer::from_image(&load(&save(&dump, Format::Binary).unwrap()).unwrap());
+        assert_eq!(restored.to_image(), dump);
+        restored.add_input(7);
+        assert_eq!(restored.run_until_output(1).unwrap(), vec![14]);
+    }
+}
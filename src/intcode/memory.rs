@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+/// Words per page of the dense part of `PagedMemory`.
+const PAGE_SIZE: usize = 1024;
+/// Addresses below this are kept in pages, anything above in a hash map.
+const DENSE_LIMIT: usize = 1024 * PAGE_SIZE;
+
+/// Returned when a write would take memory usage past its limit.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OutOfMemory;
+
+/// Word addressable memory where every address starts out as zero.
+pub trait Memory {
+    fn read(&self, address: usize) -> i64;
+    fn write(&mut self, address: usize, value: i64) -> Result<(), OutOfMemory>;
+}
+
+/// A slice is a fixed size memory: reads past the end give zero and writes past
+/// the end fail.
+impl Memory for [i64] {
+    fn read(&self, address: usize) -> i64 {
+        self.get(address).copied().unwrap_or(0)
+    }
+
+    fn write(&mut self, address: usize, value: i64) -> Result<(), OutOfMemory> {
+        *self.get_mut(address).ok_or(OutOfMemory)? = value;
+        Ok(())
+    }
+}
+
+/// Pages are allocated on first write for the low addresses programs normally
+/// use, and far addresses are stored one word at a time.
+#[derive(Debug, Clone, Default)]
+pub struct PagedMemory {
+    pages: Vec<Option<Box<[i64]>>>,
+    far: HashMap<usize, i64>,
+    /// One past the highest address written in the dense range.
+    len: usize,
+    /// Maximum number of words that may be allocated, if limited.
+    limit: Option<usize>,
+}
+
+impl PagedMemory {
+    pub fn from_words(words: &[i64]) -> PagedMemory {
+        let mut memory = PagedMemory::default();
+        for (address, value) in words.iter().enumerate() {
+            memory.write(address, *value).unwrap();
+        }
+        memory.len = memory.len.max(words.len());
+        memory
+    }
+
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+    }
+
+    /// Number of words currently allocated.
+    pub fn allocated(&self) -> usize {
+        self.pages.iter().flatten().count() * PAGE_SIZE + self.far.len()
+    }
+
+    /// The words from address 0 up to the highest address written below the
+    /// far range.
+    pub fn dense(&self) -> Vec<i64> {
+        (0..self.len).map(|address| self.read(address)).collect()
+    }
+
+    /// Words stored at far addresses, in address order.
+    pub fn far_entries(&self) -> Vec<(usize, i64)> {
+        let mut entries: Vec<(usize, i64)> = self.far.iter().map(|(a, v)| (*a, *v)).collect();
+        entries.sort();
+        entries
+    }
+
+    fn reserve(&self, words: usize) -> Result<(), OutOfMemory> {
+        match self.limit {
+            Some(limit) if self.allocated() + words > limit => Err(OutOfMemory),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Memory for PagedMemory {
+    fn read(&self, address: usize) -> i64 {
+        if address >= DENSE_LIMIT {
+            return self.far.get(&address).copied().unwrap_or(0);
+        }
+        match self.pages.get(address / PAGE_SIZE) {
+            Some(Some(page)) => page[address % PAGE_SIZE],
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, address: usize, value: i64) -> Result<(), OutOfMemory> {
+        if address >= DENSE_LIMIT {
+            if !self.far.contains_key(&address) {
+                self.reserve(1)?;
+            }
+            self.far.insert(address, value);
+            return Ok(());
+        }
+        let index = address / PAGE_SIZE;
+        if index >= self.pages.len() {
+            self.pages.resize(index + 1, None);
+        }
+        if self.pages[index].is_none() {
+            self.reserve(PAGE_SIZE)?;
+            self.pages[index] = Some(vec![0; PAGE_SIZE].into_boxed_slice());
+        }
+        self.pages[index].as_mut().unwrap()[address % PAGE_SIZE] = value;
+        self.len = self.len.max(address + 1);
+        Ok(())
+    }
+}
+
+/// Memories are equal when every address reads the same, however they are allocated.
+impl PartialEq for PagedMemory {
+    fn eq(&self, other: &PagedMemory) -> bool {
+        (0..self.len.max(other.len)).all(|address| self.read(address) == other.read(address))
+            && self.far_entries() == other.far_entries()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::memory::Memory;
+    use crate::intcode::memory::OutOfMemory;
+    use crate::intcode::memory::PagedMemory;
+
+    #[test]
+    fn test_sparse_writes() {
+        let mut memory = PagedMemory::from_words(&[1, 2, 3]);
+        memory.write(5000, 7).unwrap();
+        memory.write(1_000_000_000, 8).unwrap();
+        assert_eq!(memory.read(2), 3);
+        assert_eq!(memory.read(4999), 0);
+        assert_eq!(memory.read(5000), 7);
+        assert_eq!(memory.read(1_000_000_000), 8);
+        assert_eq!(memory.read(usize::MAX), 0);
+        assert_eq!(memory.dense().len(), 5001);
+        assert_eq!(memory.far_entries(), vec![(1_000_000_000, 8)]);
+        assert_eq!(memory.allocated(), 2 * 1024 + 1);
+    }
+
+    #[test]
+    fn test_limit() {
+        let mut memory = PagedMemory::from_words(&[1, 2, 3]);
+        memory.set_limit(Some(1025));
+        assert_eq!(memory.write(1000, 1), Ok(()));
+        assert_eq!(memory.write(1 << 40, 1), Ok(()));
+        assert_eq!(memory.write(1 << 40, 2), Ok(()));
+        assert_eq!(memory.write(1 << 41, 1), Err(OutOfMemory));
+        assert_eq!(memory.write(2000, 1), Err(OutOfMemory));
+        assert_eq!(memory.read(2000), 0);
+    }
+}
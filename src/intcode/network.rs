@@ -0,0 +1,516 @@
+use crate::intcode::channels::InputFn;
+use crate::intcode::channels::OutputFn;
+use crate::intcode::IntCodeComputer;
+use crate::intcode::IntcodeError;
+use crate::intcode::State;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::channel;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+use std::sync::mpsc::TryRecvError;
+use std::sync::Arc;
+use std::thread;
+
+pub const NAT_ADDRESS: i64 = 255;
+
+/// Number of instructions a node may execute per turn of the round-robin scheduler.
+const SLICE_STEPS: usize = 10_000;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Packet {
+    pub destination: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+/// What the network has observed so far, used to decide when to stop.
+#[derive(Debug, Default)]
+pub struct Traffic {
+    pub last_values: Vec<Option<i64>>,
+    pub packets_routed: usize,
+    pub nat_received: Vec<Packet>,
+    pub nat_sent: Vec<Packet>,
+    pub undeliverable: Vec<Packet>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum NetworkStatus {
+    /// The stop condition was met.
+    Stopped,
+    /// Every node has halted.
+    Halted,
+    /// Every node is waiting for input and there is nothing left to deliver.
+    Idle,
+}
+
+pub enum Scheduler {
+    RoundRobin,
+    ThreadPerMachine,
+}
+
+enum Routing {
+    /// Every value a node outputs is passed on to a fixed node.
+    Pipe(Vec<usize>),
+    /// Nodes output (destination, X, Y) triples and read -1 when they have no packets.
+    Packets,
+}
+
+struct Node {
+    computer: IntCodeComputer,
+    queue: VecDeque<i64>,
+    pending: Vec<i64>,
+    idle: bool,
+}
+
+struct Router {
+    routing: Routing,
+    size: usize,
+    nat: bool,
+    traffic: Traffic,
+}
+
+pub struct IntcodeNetwork {
+    nodes: Vec<Node>,
+    router: Router,
+}
+
+enum Event {
+    Output(usize, i64),
+    Idle(usize, usize, usize),
+    Stopped(usize, Result<(), IntcodeError>),
+}
+
+impl Router {
+    fn is_packet_switched(&self) -> bool {
+        matches!(self.routing, Routing::Packets)
+    }
+
+    /// Routes a value output by `source`, using `pending` to assemble packets.
+    /// Returns the node and values to deliver, if any.
+    fn route(
+        &mut self,
+        source: usize,
+        pending: &mut Vec<i64>,
+        value: i64,
+    ) -> Option<(usize, Vec<i64>)> {
+        self.traffic.last_values[source] = Some(value);
+        match &self.routing {
+            Routing::Pipe(destinations) => Some((destinations[source], vec![value])),
+            Routing::Packets => {
+                pending.push(value);
+                if pending.len() < 3 {
+                    return None;
+                }
+                let packet = Packet {
+                    destination: pending[0],
+                    x: pending[1],
+                    y: pending[2],
+                };
+                pending.clear();
+                self.traffic.packets_routed += 1;
+                if self.nat && packet.destination == NAT_ADDRESS {
+                    self.traffic.nat_received.push(packet);
+                    None
+                } else if packet.destination >= 0 && (packet.destination as usize) < self.size {
+                    Some((packet.destination as usize, vec![packet.x, packet.y]))
+                } else {
+                    self.traffic.undeliverable.push(packet);
+                    None
+                }
+            }
+        }
+    }
+
+    /// Hands the last packet the NAT received to node 0, if there is one.
+    fn wake_from_nat(&mut self) -> Option<(usize, Vec<i64>)> {
+        if !self.nat {
+            return None;
+        }
+        let last = *self.traffic.nat_received.last()?;
+        let packet = Packet {
+            destination: 0,
+            ..last
+        };
+        self.traffic.nat_sent.push(packet);
+        Some((0, vec![packet.x, packet.y]))
+    }
+}
+
+impl IntcodeNetwork {
+    /// One copy of `program` per first input value, which each node reads first.
+    fn new(program: &IntCodeComputer, first_inputs: Vec<i64>, routing: Routing) -> IntcodeNetwork {
+        let size = first_inputs.len();
+        IntcodeNetwork {
+            nodes: first_inputs
+                .into_iter()
+                .map(|value| Node {
+                    computer: program.clone(),
+                    queue: VecDeque::from(vec![value]),
+                    pending: Vec::new(),
+                    idle: false,
+                })
+                .collect(),
+            router: Router {
+                routing,
+                size,
+                nat: false,
+                traffic: Traffic {
+                    last_values: vec![None; size],
+                    ..Default::default()
+                },
+            },
+        }
+    }
+
+    /// Machines connected in a loop, each fed its phase setting first, like the
+    /// day 7 amplifier feedback loop.
+    pub fn ring(program: &IntCodeComputer, phases: &[i64]) -> IntcodeNetwork {
+        let destinations = (0..phases.len()).map(|i| (i + 1) % phases.len()).collect();
+        IntcodeNetwork::new(program, phases.to_vec(), Routing::Pipe(destinations))
+    }
+
+    /// `size` copies of a program exchanging packets, each fed its own address first.
+    pub fn packet_switched(program: &IntCodeComputer, size: usize) -> IntcodeNetwork {
+        IntcodeNetwork::new(program, (0..size as i64).collect(), Routing::Packets)
+    }
+
+    /// Adds a NAT at address 255 that keeps the last packet sent to it and
+    /// sends it to node 0 whenever the network goes idle.
+    pub fn with_nat(mut self) -> IntcodeNetwork {
+        self.router.nat = true;
+        self
+    }
+
+    pub fn send(&mut self, node: usize, value: i64) {
+        self.nodes[node].queue.push_back(value);
+    }
+
+    pub fn get_traffic(&self) -> &Traffic {
+        &self.router.traffic
+    }
+
+    fn all_halted(&self) -> bool {
+        self.nodes
+            .iter()
+            .all(|node| node.computer.state == State::Halted)
+    }
+
+    /// Runs until `stop` returns true, every node halts, or the network is idle
+    /// with nothing for the NAT to send.
+    pub fn run(
+        &mut self,
+        scheduler: Scheduler,
+        stop: impl Fn(&Traffic) -> bool,
+    ) -> Result<NetworkStatus, IntcodeError> {
+        match scheduler {
+            Scheduler::RoundRobin => self.run_round_robin(stop),
+            Scheduler::ThreadPerMachine => self.run_threaded(stop),
+        }
+    }
+
+    fn run_slice(&mut self, index: usize) -> Result<Vec<i64>, IntcodeError> {
+        let nic = self.router.is_packet_switched();
+        let node = &mut self.nodes[index];
+        let mut output = Vec::new();
+        node.idle = false;
+        node.computer.resume();
+        let mut steps = 0;
+        while node.computer.state == State::Running && !node.idle && steps < SLICE_STEPS {
+            let queue = &mut node.queue;
+            let idle = &mut node.idle;
+            node.computer.execute_step_with(
+                &mut InputFn(|| match queue.pop_front() {
+                    Some(value) => Some(value),
+                    None => {
+                        *idle = true;
+                        if nic {
+                            Some(-1)
+                        } else {
+                            None
+                        }
+                    }
+                }),
+                &mut OutputFn(|value| output.push(value)),
+            )?;
+            steps += 1;
+        }
+        Ok(output)
+    }
+
+    fn run_round_robin(
+        &mut self,
+        stop: impl Fn(&Traffic) -> bool,
+    ) -> Result<NetworkStatus, IntcodeError> {
+        loop {
+            let mut delivered = false;
+            for index in 0..self.nodes.len() {
+                for value in self.run_slice(index)? {
+                    let pending = &mut self.nodes[index].pending;
+                    if let Some((destination, values)) = self.router.route(index, pending, value) {
+                        self.nodes[destination].queue.extend(values);
+                        delivered = true;
+                    }
+                }
+                if stop(&self.router.traffic) {
+                    return Ok(NetworkStatus::Stopped);
+                }
+            }
+            if self.all_halted() {
+                return Ok(NetworkStatus::Halted);
+            }
+            let idle = !delivered
+                && self.nodes.iter().all(|node| {
+                    node.queue.is_empty() && (node.idle || node.computer.state == State::Halted)
+                });
+            if idle {
+                match self.router.wake_from_nat() {
+                    Some((destination, values)) => self.nodes[destination].queue.extend(values),
+                    None => return Ok(NetworkStatus::Idle),
+                }
+                if stop(&self.router.traffic) {
+                    return Ok(NetworkStatus::Stopped);
+                }
+            }
+        }
+    }
+
+    fn spawn_node(
+        index: usize,
+        mut node: Node,
+        nic: bool,
+        inbox: Receiver<Vec<i64>>,
+        events: Sender<Event>,
+        stopping: Arc<AtomicBool>,
+    ) -> thread::JoinHandle<Node> {
+        thread::spawn(move || {
+            let mut consumed = 0;
+            let produced = Cell::new(0);
+            let mut reported = None;
+            let output_events = events.clone();
+            let mut input = InputFn(|| loop {
+                if stopping.load(Ordering::SeqCst) {
+                    return None;
+                }
+                if let Some(value) = node.queue.pop_front() {
+                    consumed += 1;
+                    return Some(value);
+                }
+                match inbox.try_recv() {
+                    Ok(values) => {
+                        node.queue.extend(values);
+                        continue;
+                    }
+                    Err(TryRecvError::Disconnected) => return None,
+                    Err(TryRecvError::Empty) => {}
+                }
+                if reported != Some((consumed, produced.get())) {
+                    reported = Some((consumed, produced.get()));
+                    let _ = events.send(Event::Idle(index, consumed, produced.get()));
+                }
+                if nic {
+                    thread::yield_now();
+                    return Some(-1);
+                }
+                match inbox.recv() {
+                    Ok(values) => node.queue.extend(values),
+                    Err(_) => return None,
+                }
+            });
+            let mut output = OutputFn(|value| {
+                produced.set(produced.get() + 1);
+                let _ = output_events.send(Event::Output(index, value));
+            });
+            // Machines have a thread each, so there is nothing to yield to
+            let result = loop {
+                let result = node.computer.execute_with(&mut input, &mut output);
+                if node.computer.state != State::Yielded {
+                    break result;
+                }
+            };
+            let _ = output_events.send(Event::Stopped(index, result));
+            node.queue.extend(inbox.try_iter().flatten());
+            node
+        })
+    }
+
+    fn run_threaded(
+        &mut self,
+        stop: impl Fn(&Traffic) -> bool,
+    ) -> Result<NetworkStatus, IntcodeError> {
+        let nic = self.router.is_packet_switched();
+        let size = self.nodes.len();
+        let stopping = Arc::new(AtomicBool::new(false));
+        let (event_sender, events) = channel();
+        let mut inboxes = Vec::new();
+        let mut handles = Vec::new();
+        // Packets are assembled here while the nodes themselves are in their threads
+        let mut pending = Vec::new();
+        let mut delivered = Vec::new();
+        for (index, mut node) in std::mem::take(&mut self.nodes).into_iter().enumerate() {
+            pending.push(std::mem::take(&mut node.pending));
+            delivered.push(node.queue.len());
+            let (sender, receiver) = channel();
+            inboxes.push(sender);
+            handles.push(IntcodeNetwork::spawn_node(
+                index,
+                node,
+                nic,
+                receiver,
+                event_sender.clone(),
+                stopping.clone(),
+            ));
+        }
+        drop(event_sender);
+
+        let mut received = vec![0; size];
+        let mut idle_reports: Vec<Option<(usize, usize)>> = vec![None; size];
+        let mut halted = vec![false; size];
+        let mut result = Ok(NetworkStatus::Halted);
+        while let Ok(event) = events.recv() {
+            match event {
+                Event::Output(index, value) => {
+                    received[index] += 1;
+                    let routed = self.router.route(index, &mut pending[index], value);
+                    if let Some((destination, values)) = routed {
+                        delivered[destination] += values.len();
+                        let _ = inboxes[destination].send(values);
+                    }
+                }
+                Event::Idle(index, consumed, produced) => {
+                    idle_reports[index] = Some((consumed, produced));
+                }
+                Event::Stopped(index, outcome) => {
+                    if let Err(error) = outcome {
+                        result = Err(error);
+                        break;
+                    }
+                    halted[index] = true;
+                }
+            }
+            if stop(&self.router.traffic) {
+                result = Ok(NetworkStatus::Stopped);
+                break;
+            }
+            if halted.iter().all(|x| *x) {
+                result = Ok(NetworkStatus::Halted);
+                break;
+            }
+            let idle = (0..size)
+                .all(|i| halted[i] || idle_reports[i] == Some((delivered[i], received[i])));
+            if idle {
+                match self.router.wake_from_nat() {
+                    Some((destination, values)) => {
+                        delivered[destination] += values.len();
+                        let _ = inboxes[destination].send(values);
+                    }
+                    None => {
+                        result = Ok(NetworkStatus::Idle);
+                        break;
+                    }
+                }
+                if stop(&self.router.traffic) {
+                    result = Ok(NetworkStatus::Stopped);
+                    break;
+                }
+            }
+        }
+
+        stopping.store(true, Ordering::SeqCst);
+        drop(inboxes);
+        for (handle, pending) in handles.into_iter().zip(pending) {
+            let mut node = handle.join().expect("Network node panicked");
+            node.pending = pending;
+            self.nodes.push(node);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::assembler::assemble;
+    use crate::intcode::network::IntcodeNetwork;
+    use crate::intcode::network::NetworkStatus;
+    use crate::intcode::network::Scheduler;
+    use crate::intcode::IntCodeComputer;
+
+    /// Node 0 sends (10, 20) to node 1. Every node forwards the packets it
+    /// receives to the NAT with Y incremented.
+    const RELAY: &str = "
+               IN [address]
+               JNZ [address], #loop
+               OUT #1
+               OUT #10
+               OUT #20
+        loop:  IN [x]
+               EQ [x], #-1, [empty]
+               JNZ [empty], #loop
+               IN [y]
+               ADD [y], #1, [y]
+               OUT #255
+               OUT [x]
+               OUT [y]
+               JZ #0, #loop
+        address: .data 0
+        x:     .data 0
+        y:     .data 0
+        empty: .data 0
+    ";
+
+    fn relay_network() -> IntcodeNetwork {
+        let program = IntCodeComputer::from_words(&assemble(RELAY).unwrap());
+        IntcodeNetwork::packet_switched(&program, 3).with_nat()
+    }
+
+    fn check_nat(scheduler: Scheduler) {
+        let mut network = relay_network();
+        let status = network.run(scheduler, |traffic| traffic.nat_sent.len() >= 3);
+        assert_eq!(status, Ok(NetworkStatus::Stopped));
+        let traffic = network.get_traffic();
+        let sent: Vec<(i64, i64, i64)> = traffic
+            .nat_sent
+            .iter()
+            .map(|x| (x.destination, x.x, x.y))
+            .collect();
+        assert_eq!(sent, vec![(0, 10, 21), (0, 10, 22), (0, 10, 23)]);
+        assert_eq!(traffic.nat_received[0].y, 21);
+        assert!(traffic.undeliverable.is_empty());
+    }
+
+    #[test]
+    fn test_nat_round_robin() {
+        check_nat(Scheduler::RoundRobin);
+    }
+
+    #[test]
+    fn test_nat_thread_per_machine() {
+        check_nat(Scheduler::ThreadPerMachine);
+    }
+
+    #[test]
+    fn test_idle_without_nat() {
+        let program = IntCodeComputer::from_words(&assemble(RELAY).unwrap());
+        let mut network = IntcodeNetwork::packet_switched(&program, 2);
+        assert_eq!(
+            network.run(Scheduler::RoundRobin, |_| false),
+            Ok(NetworkStatus::Idle)
+        );
+        assert_eq!(network.get_traffic().undeliverable.len(), 1);
+    }
+
+    #[test]
+    fn test_amplifier_ring() {
+        let program = IntCodeComputer::read_program(
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5",
+        );
+        for scheduler in [Scheduler::RoundRobin, Scheduler::ThreadPerMachine] {
+            let mut network = IntcodeNetwork::ring(&program, &[9, 8, 7, 6, 5]);
+            network.send(0, 0);
+            assert_eq!(network.run(scheduler, |_| false), Ok(NetworkStatus::Halted));
+            assert_eq!(network.get_traffic().last_values[4], Some(139629729));
+        }
+    }
+}
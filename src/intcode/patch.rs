@@ -0,0 +1,351 @@
+use crate::intcode::IntCodeComputer;
+use std::fmt;
+use std::path::Path;
+
+/// One memory cell, with the value it must have before the patch is applied.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Change {
+    pub address: usize,
+    pub old: i64,
+    pub new: i64,
+}
+
+/// Changes to a program's memory. In text form every change is a line
+/// `address: old -> new`, and `#` starts a comment.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Patch {
+    pub changes: Vec<Change>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PatchError {
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// Memory does not hold the value the patch expects, so the patch was
+    /// probably written for a different program.
+    Mismatch {
+        address: usize,
+        expected: i64,
+        found: i64,
+    },
+    UnknownPatch {
+        name: String,
+        available: Vec<String>,
+    },
+    Io {
+        path: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatchError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            PatchError::Mismatch {
+                address,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {} at address {}, found {}",
+                expected, address, found
+            ),
+            PatchError::UnknownPatch { name, available } => write!(
+                f,
+                "no patch named '{}', available: {}",
+                name,
+                available.join(", ")
+            ),
+            PatchError::Io { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+fn parse_change(text: &str) -> Result<Change, String> {
+    let (address, values) = text
+        .split_once(':')
+        .ok_or("expected 'address: old -> new'")?;
+    let (old, new) = values
+        .split_once("->")
+        .ok_or("expected 'address: old -> new'")?;
+    let number = |x: &str| {
+        x.trim()
+            .parse::<i64>()
+            .map_err(|_| format!("invalid number '{}'", x.trim()))
+    };
+    let address = address
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("invalid address '{}'", address.trim()))?;
+    Ok(Change {
+        address,
+        old: number(old)?,
+        new: number(new)?,
+    })
+}
+
+/// Strips the comment from a line, if it has one.
+fn content(line: &str) -> &str {
+    line.split('#').next().unwrap().trim()
+}
+
+impl Patch {
+    /// Parses the changes in `text`, numbering lines from `first_line`.
+    fn parse_lines<'a>(
+        lines: impl Iterator<Item = &'a str>,
+        first_line: usize,
+    ) -> Result<Patch, PatchError> {
+        let mut patch = Patch::default();
+        for (index, line) in lines.enumerate() {
+            let line_number = first_line + index;
+            let text = content(line);
+            if text.is_empty() {
+                continue;
+            }
+            let change = parse_change(text).map_err(|message| PatchError::Syntax {
+                line: line_number,
+                message,
+            })?;
+            if patch.changes.iter().any(|x| x.address == change.address) {
+                return Err(PatchError::Syntax {
+                    line: line_number,
+                    message: format!("address {} is changed twice", change.address),
+                });
+            }
+            patch.changes.push(change);
+        }
+        Ok(patch)
+    }
+
+    pub fn parse(text: &str) -> Result<Patch, PatchError> {
+        Patch::parse_lines(text.lines(), 1)
+    }
+
+    /// The patch that turns `before` into `after`. Memory past the end of the
+    /// shorter one counts as 0.
+    pub fn diff(before: &[i64], after: &[i64]) -> Patch {
+        let value = |memory: &[i64], address: usize| memory.get(address).copied().unwrap_or(0);
+        let changes = (0..before.len().max(after.len()))
+            .filter(|x| value(before, *x) != value(after, *x))
+            .map(|address| Change {
+                address,
+                old: value(before, address),
+                new: value(after, address),
+            })
+            .collect();
+        Patch { changes }
+    }
+}
+
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in self.changes.iter() {
+            writeln!(f, "{}: {} -> {}", change.address, change.old, change.new)?;
+        }
+        Ok(())
+    }
+}
+
+/// Named patches, each starting with a `[name]` line.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct PatchSet {
+    patches: Vec<(String, Patch)>,
+}
+
+impl PatchSet {
+    pub fn parse(text: &str) -> Result<PatchSet, PatchError> {
+        let lines: Vec<&str> = text.lines().collect();
+        let headers: Vec<(usize, &str)> = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let text = content(line);
+                Some((index, text.strip_prefix('[')?.strip_suffix(']')?.trim()))
+            })
+            .collect();
+        let first = headers.first().map_or(lines.len(), |x| x.0);
+        if let Some(index) = lines[..first].iter().position(|x| !content(x).is_empty()) {
+            return Err(PatchError::Syntax {
+                line: index + 1,
+                message: "change before the first [name] line".to_string(),
+            });
+        }
+        let mut set = PatchSet::default();
+        for (position, (index, name)) in headers.iter().enumerate() {
+            let end = headers.get(position + 1).map_or(lines.len(), |x| x.0);
+            if set.patches.iter().any(|x| x.0 == *name) {
+                return Err(PatchError::Syntax {
+                    line: index + 1,
+                    message: format!("patch '{}' is defined twice", name),
+                });
+            }
+            let patch = Patch::parse_lines(lines[index + 1..end].iter().copied(), index + 2)?;
+            set.patches.push((name.to_string(), patch));
+        }
+        Ok(set)
+    }
+
+    /// Reads the patches kept next to a program, see `patch_path`.
+    pub fn for_program(program: &str) -> Result<PatchSet, PatchError> {
+        let path = patch_path(program);
+        let text = std::fs::read_to_string(&path).map_err(|error| PatchError::Io {
+            path: path.clone(),
+            message: error.to_string(),
+        })?;
+        PatchSet::parse(&text)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.patches.iter().map(|x| x.0.clone()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Patch, PatchError> {
+        self.patches
+            .iter()
+            .find(|x| x.0 == name)
+            .map(|x| &x.1)
+            .ok_or_else(|| PatchError::UnknownPatch {
+                name: name.to_string(),
+                available: self.names(),
+            })
+    }
+}
+
+/// Where the patches for a program are kept: the program's path with every
+/// extension replaced by `.patch`, so `data/day13.txt` and `data/day13.txt.gz`
+/// share `data/day13.patch`.
+pub fn patch_path(program: &str) -> String {
+    let path = Path::new(program);
+    let name = path.file_name().map_or("", |x| x.to_str().unwrap_or(""));
+    let stem = name.split('.').next().unwrap();
+    path.with_file_name(format!("{}.patch", stem))
+        .to_string_lossy()
+        .to_string()
+}
+
+impl IntCodeComputer {
+    /// Applies every change, or none of them if any cell does not hold the
+    /// value the patch expects.
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<(), PatchError> {
+        for change in patch.changes.iter() {
+            let found = self.get_value(change.address);
+            if found != change.old {
+                return Err(PatchError::Mismatch {
+                    address: change.address,
+                    expected: change.old,
+                    found,
+                });
+            }
+        }
+        for change in patch.changes.iter() {
+            self.set_value(change.address, change.new);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input_files::read_content;
+    use crate::intcode::patch::patch_path;
+    use crate::intcode::patch::Change;
+    use crate::intcode::patch::Patch;
+    use crate::intcode::patch::PatchError;
+    use crate::intcode::patch::PatchSet;
+    use crate::intcode::IntCodeComputer;
+    use crate::intcode::Outcome;
+
+    #[test]
+    fn test_parse() {
+        let set =
+            PatchSet::parse("# Header\n\n[one]\n1: 0 -> 12 # noun\n2:0->-2\n[two]\n").unwrap();
+        assert_eq!(set.names(), vec!["one", "two"]);
+        assert_eq!(
+            set.get("one").unwrap().changes[1],
+            Change {
+                address: 2,
+                old: 0,
+                new: -2
+            }
+        );
+        assert_eq!(set.get("two").unwrap(), &Patch::default());
+        assert_eq!(
+            set.get("three").unwrap_err().to_string(),
+            "no patch named 'three', available: one, two"
+        );
+        assert_eq!(
+            PatchSet::parse("[one]\n1: 0 -> 1\n\n1: 1 -> 2\n").unwrap_err(),
+            PatchError::Syntax {
+                line: 4,
+                message: "address 1 is changed twice".to_string()
+            }
+        );
+        assert_eq!(
+            Patch::parse("1: x -> 2").unwrap_err().to_string(),
+            "line 1: invalid number 'x'"
+        );
+        assert_eq!(patch_path("data/day13.txt.gz"), "data/day13.patch");
+    }
+
+    #[test]
+    fn test_apply_checks_old_values() {
+        let mut m = IntCodeComputer::read_program("1,0,0,0,99");
+        let patch = Patch::parse("1: 0 -> 4\n2: 1 -> 4").unwrap();
+        assert_eq!(
+            m.apply_patch(&patch),
+            Err(PatchError::Mismatch {
+                address: 2,
+                expected: 1,
+                found: 0
+            })
+        );
+        assert_eq!(m.get_memory(), vec![1, 0, 0, 0, 99]);
+        let before = m.get_memory();
+        m.apply_patch(&Patch::parse("1: 0 -> 4\n2: 0 -> 4").unwrap())
+            .unwrap();
+        m.execute_until_halted().unwrap();
+        assert_eq!(
+            Patch::diff(&before, &m.get_memory()).to_string(),
+            "0: 1 -> 198\n1: 0 -> 4\n2: 0 -> 4\n"
+        );
+        assert_eq!(Patch::diff(&[1], &[1, 0, 5]).changes.len(), 1);
+    }
+
+    #[test]
+    fn test_skip_self_test() {
+        let content = read_content("data/day05.txt").unwrap();
+        let mut m = IntCodeComputer::read_program_with_input(&content, 1);
+        let patches = PatchSet::parse(include_str!("../../data/day05.patch")).unwrap();
+        m.apply_patch(patches.get("skip self-test").unwrap())
+            .unwrap();
+        m.execute_until_halted().unwrap();
+        assert_eq!(m.take_output(), vec![8332629]);
+    }
+
+    #[test]
+    fn test_infinite_paddle() {
+        // With the whole row as paddle the joystick never needs to move
+        let content = read_content("data/day13.txt").unwrap();
+        let mut m = IntCodeComputer::read_program(&content);
+        let patches = PatchSet::parse(include_str!("../../data/day13.patch")).unwrap();
+        for name in ["free play", "infinite paddle"] {
+            m.apply_patch(patches.get(name).unwrap()).unwrap();
+        }
+        let mut score = 0;
+        while m.execute_until_stopped_within(Default::default()) == Ok(Outcome::Waiting) {
+            m.add_input(0);
+        }
+        for tile in m.take_output().chunks(3) {
+            if tile[0] == -1 && tile[1] == 0 {
+                score = tile[2];
+            }
+        }
+        assert!(m.has_terminated());
+        assert_eq!(score, 15973);
+    }
+}
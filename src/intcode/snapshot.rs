@@ -0,0 +1,377 @@
+use crate::intcode::memory::Memory;
+use crate::intcode::memory::PagedMemory;
+use crate::intcode::IntCodeComputer;
+use crate::intcode::IntcodeError;
+use crate::intcode::State;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// First line of every snapshot file. The number is bumped whenever the format changes.
+const HEADER: &str = "intcode-snapshot";
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+#[derive(Debug, PartialEq)]
+pub struct SnapshotError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+fn join(values: impl Iterator<Item = i64>) -> String {
+    values.map(|x| x.to_string()).collect::<Vec<_>>().join(",")
+}
+
+fn render_state(state: &State) -> String {
+    match state {
+        State::Running => "running".to_string(),
+        State::Waiting => "waiting".to_string(),
+        State::Yielded => "yielded".to_string(),
+        State::Halted => "halted".to_string(),
+        State::Faulted(error) => {
+            let (kind, ip, instruction, extra) = match error {
+                IntcodeError::InvalidOpcode { ip, instruction } => {
+                    ("invalid_opcode", ip, instruction, None)
+                }
+                IntcodeError::InvalidMode {
+                    ip,
+                    instruction,
+                    mode,
+                } => ("invalid_mode", ip, instruction, Some(*mode)),
+                IntcodeError::WriteToImmediate { ip, instruction } => {
+                    ("write_to_immediate", ip, instruction, None)
+                }
+                IntcodeError::NegativeAddress {
+                    ip,
+                    instruction,
+                    address,
+                } => ("negative_address", ip, instruction, Some(*address)),
+                IntcodeError::InputExhausted { ip, instruction } => {
+                    ("input_exhausted", ip, instruction, None)
+                }
+                IntcodeError::Overflow { ip, instruction } => ("overflow", ip, instruction, None),
+                IntcodeError::MemoryLimit {
+                    ip,
+                    instruction,
+                    address,
+                } => ("memory_limit", ip, instruction, Some(*address as i64)),
+                IntcodeError::Trap { ip, instruction } => ("trap", ip, instruction, None),
+                IntcodeError::TraceFailed { ip, message } => {
+                    return format!("faulted trace_failed {} {}", ip, message);
+                }
+                IntcodeError::OutputFailed { ip, message } => {
+                    return format!("faulted output_failed {} {}", ip, message);
+                }
+            };
+            match extra {
+                Some(extra) => format!("faulted {} {} {} {}", kind, ip, instruction, extra),
+                None => format!("faulted {} {} {}", kind, ip, instruction),
+            }
+        }
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.trim()
+        .parse::<T>()
+        .map_err(|_| format!("Invalid number '{}'", text.trim()))
+}
+
+fn parse_list(text: &str) -> Result<Vec<i64>, String> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    text.split(',').map(parse_number).collect()
+}
+
+fn parse_memory(text: &str) -> Result<PagedMemory, String> {
+    let memory = parse_list(text)?;
+    if memory.is_empty() {
+        return Err("Empty memory".to_string());
+    }
+    Ok(PagedMemory::from_words(&memory))
+}
+
+/// Far memory is written as `address=value` pairs.
+fn parse_far(text: &str) -> Result<Vec<(usize, i64)>, String> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    text.split(',')
+        .map(|entry| {
+            let (address, value) = entry
+                .split_once('=')
+                .ok_or(format!("Invalid far memory entry '{}'", entry.trim()))?;
+            Ok((parse_number(address)?, parse_number(value)?))
+        })
+        .collect()
+}
+
+fn parse_optional(text: &str) -> Result<Option<i64>, String> {
+    match text.trim() {
+        "none" => Ok(None),
+        x => parse_number(x).map(Some),
+    }
+}
+
+fn parse_state(text: &str) -> Result<State, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let number = |index: usize| -> Result<i64, String> {
+        parse_number(words.get(index).ok_or("Incomplete fault description")?)
+    };
+    match words.as_slice() {
+        ["running"] => Ok(State::Running),
+        ["waiting"] => Ok(State::Waiting),
+        ["yielded"] => Ok(State::Yielded),
+        ["halted"] => Ok(State::Halted),
+        ["faulted", "trace_failed", ip, message @ ..] => {
+            Ok(State::Faulted(IntcodeError::TraceFailed {
+                ip: parse_number(ip)?,
+                message: message.join(" "),
+            }))
+        }
+        ["faulted", "output_failed", ip, message @ ..] => {
+            Ok(State::Faulted(IntcodeError::OutputFailed {
+                ip: parse_number(ip)?,
+                message: message.join(" "),
+            }))
+        }
+        ["faulted", kind, ..] => {
+            let ip = usize::try_from(number(2)?).map_err(|_| "Invalid fault address")?;
+            let instruction = number(3)?;
+            let error = match *kind {
+                "invalid_opcode" => IntcodeError::InvalidOpcode { ip, instruction },
+                "invalid_mode" => IntcodeError::InvalidMode {
+                    ip,
+                    instruction,
+                    mode: number(4)?,
+                },
+                "write_to_immediate" => IntcodeError::WriteToImmediate { ip, instruction },
+                "negative_address" => IntcodeError::NegativeAddress {
+                    ip,
+                    instruction,
+                    address: number(4)?,
+                },
+                "input_exhausted" => IntcodeError::InputExhausted { ip, instruction },
+                "overflow" => IntcodeError::Overflow { ip, instruction },
+                "memory_limit" => IntcodeError::MemoryLimit {
+                    ip,
+                    instruction,
+                    address: usize::try_from(number(4)?).map_err(|_| "Invalid fault address")?,
+                },
+                "trap" => IntcodeError::Trap { ip, instruction },
+                _ => return Err(format!("Unknown fault '{}'", kind)),
+            };
+            Ok(State::Faulted(error))
+        }
+        _ => Err(format!("Unknown state '{}'", text.trim())),
+    }
+}
+
+/// The `key value` lines of a snapshot, with the line number each one came from.
+struct Fields<'a> {
+    values: HashMap<&'a str, (usize, &'a str)>,
+    last_line: usize,
+}
+
+impl<'a> Fields<'a> {
+    fn parse<T>(
+        &self,
+        key: &str,
+        parser: fn(&str) -> Result<T, String>,
+    ) -> Result<T, SnapshotError> {
+        let (line, value) = self.values.get(key).ok_or(SnapshotError {
+            line: self.last_line,
+            message: format!("Missing field '{}'", key),
+        })?;
+        parser(value).map_err(|message| SnapshotError {
+            line: *line,
+            message,
+        })
+    }
+
+    fn parse_optional<T: Default>(
+        &self,
+        key: &str,
+        parser: fn(&str) -> Result<T, String>,
+    ) -> Result<T, SnapshotError> {
+        if self.values.contains_key(key) {
+            self.parse(key, parser)
+        } else {
+            Ok(T::default())
+        }
+    }
+}
+
+/// Returns true if `content` looks like a snapshot rather than a program.
+pub fn is_snapshot(content: &str) -> bool {
+    content.starts_with(HEADER)
+}
+
+impl IntCodeComputer {
+    /// Serialises the complete machine state, including queued input and
+    /// output that has not been read yet, in a line based text format.
+    pub fn to_snapshot(&self) -> String {
+        let last_output = match self.last_output {
+            Some(x) => x.to_string(),
+            None => "none".to_string(),
+        };
+        let far: Vec<String> = self
+            .memory
+            .far_entries()
+            .iter()
+            .map(|(address, value)| format!("{}={}", address, value))
+            .collect();
+        format!(
+            "{} {}\nip {}\nrelative_base {}\nstate {}\ninputs_consumed {}\ninput {}\noutput {}\nlast_output {}\nmemory {}\nfar {}\n",
+            HEADER,
+            SNAPSHOT_VERSION,
+            self.current_op,
+            self.relative_base,
+            render_state(&self.state),
+            self.inputs_consumed,
+            join(self.input.iter().copied()),
+            join(self.output.iter().copied()),
+            last_output,
+            join(self.memory.dense().into_iter()),
+            far.join(",")
+        )
+    }
+
+    /// Restores a machine written by `to_snapshot`.
+    pub fn from_snapshot(content: &str) -> Result<IntCodeComputer, SnapshotError> {
+        let mut lines = content.lines().enumerate();
+        let header = lines.next().map(|(_, x)| x).unwrap_or("");
+        let version = header
+            .strip_prefix(HEADER)
+            .map(|x| x.trim())
+            .ok_or(SnapshotError {
+                line: 1,
+                message: "Not an Intcode snapshot".to_string(),
+            })?;
+        // Version 1 had no far memory, which is read as empty
+        if version != SNAPSHOT_VERSION.to_string() && version != "1" {
+            return Err(SnapshotError {
+                line: 1,
+                message: format!("Unsupported snapshot version '{}'", version),
+            });
+        }
+
+        let mut fields = Fields {
+            values: HashMap::new(),
+            last_line: content.lines().count(),
+        };
+        for (index, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            fields.values.insert(key, (index + 1, value));
+        }
+        let mut memory = fields.parse("memory", parse_memory)?;
+        for (address, value) in fields.parse_optional("far", parse_far)? {
+            memory.write(address, value).unwrap();
+        }
+        Ok(IntCodeComputer {
+            current_op: fields.parse("ip", parse_number)?,
+            memory,
+            state: fields.parse("state", parse_state)?,
+            inputs_consumed: fields.parse("inputs_consumed", parse_number)?,
+            input: VecDeque::from(fields.parse("input", parse_list)?),
+            output: VecDeque::from(fields.parse("output", parse_list)?),
+            last_output: fields.parse("last_output", parse_optional)?,
+            relative_base: fields.parse("relative_base", parse_number)?,
+            cache: None,
+            tracer: Default::default(),
+            history: None,
+            extensions: Default::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input_files::read_content;
+    use crate::intcode::snapshot::is_snapshot;
+    use crate::intcode::IntCodeComputer;
+
+    #[test]
+    fn test_resume_from_snapshot() {
+        let content = read_content("data/day09.txt").unwrap();
+        let mut m = IntCodeComputer::read_program_with_input(&content, 2);
+        for _ in 0..1000 {
+            m.execute_step().unwrap();
+        }
+        m.add_input(7);
+        let snapshot = m.to_snapshot();
+        assert!(is_snapshot(&snapshot));
+        let mut restored = IntCodeComputer::from_snapshot(&snapshot).unwrap();
+        assert_eq!(restored, m);
+        restored.execute_until_halted().unwrap();
+        assert_eq!(restored.take_output(), vec![81348]);
+    }
+
+    #[test]
+    fn test_faulted_and_waiting_state() {
+        let mut m = IntCodeComputer::read_program("3,3,104,0,99");
+        m.execute_until_stopped().unwrap();
+        let waiting = IntCodeComputer::from_snapshot(&m.to_snapshot()).unwrap();
+        assert_eq!(waiting, m);
+
+        let mut m = IntCodeComputer::read_program("104,5,109,-3,204,0");
+        assert!(m.execute_until_stopped().is_err());
+        let restored = IntCodeComputer::from_snapshot(&m.to_snapshot()).unwrap();
+        assert_eq!(restored, m);
+        assert_eq!(restored.get_last_output(), Some(5));
+
+        let snapshot = m.to_snapshot().replace(
+            "faulted negative_address 4 204 -3",
+            "faulted trace_failed 4 broken pipe",
+        );
+        assert!(snapshot.contains("trace_failed"), "{}", snapshot);
+        let restored = IntCodeComputer::from_snapshot(&snapshot).unwrap();
+        assert_eq!(restored.to_snapshot(), snapshot);
+    }
+
+    #[test]
+    fn test_far_memory() {
+        let mut m = IntCodeComputer::read_program("1101,3,4,1000000000,99");
+        m.execute_until_halted().unwrap();
+        let snapshot = m.to_snapshot();
+        assert!(snapshot.contains("\nfar 1000000000=7\n"), "{}", snapshot);
+        let restored = IntCodeComputer::from_snapshot(&snapshot).unwrap();
+        assert_eq!(restored.get_value(1000000000), 7);
+        let old = snapshot
+            .replace("intcode-snapshot 2", "intcode-snapshot 1")
+            .replace("far 1000000000=7\n", "");
+        assert_eq!(
+            IntCodeComputer::from_snapshot(&old)
+                .unwrap()
+                .get_value(1000000000),
+            0
+        );
+    }
+
+    #[test]
+    fn test_invalid_snapshots() {
+        let snapshot = IntCodeComputer::read_program("99").to_snapshot();
+        let error = IntCodeComputer::from_snapshot("1,2,3").unwrap_err();
+        assert_eq!(error.message, "Not an Intcode snapshot");
+        let future = snapshot.replace("intcode-snapshot 2", "intcode-snapshot 3");
+        let error = IntCodeComputer::from_snapshot(&future).unwrap_err();
+        assert_eq!(error.message, "Unsupported snapshot version '3'");
+        let broken = snapshot.replace("state running", "state sleeping");
+        let error = IntCodeComputer::from_snapshot(&broken).unwrap_err();
+        assert_eq!(
+            (error.line, error.message.as_str()),
+            (4, "Unknown state 'sleeping'")
+        );
+    }
+}
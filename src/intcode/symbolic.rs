@@ -0,0 +1,494 @@
+use crate::intcode::memory::Memory;
+use crate::intcode::memory::OutOfMemory;
+use crate::intcode::IntcodeError;
+use crate::intcode::Op;
+use crate::intcode::Parameter;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+/// Instructions a symbolic run may execute before it is given up on.
+const MAX_STEPS: usize = 1_000_000;
+
+/// A value computed from constants and named unknowns.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    Const(i64),
+    Var(String),
+    Add(Rc<Expr>, Rc<Expr>),
+    Multiply(Rc<Expr>, Rc<Expr>),
+    LessThan(Rc<Expr>, Rc<Expr>),
+    Equals(Rc<Expr>, Rc<Expr>),
+    /// A read through an address that depends on unknowns. It cannot be
+    /// evaluated, because memory may have changed since.
+    Load(Rc<Expr>),
+}
+
+/// `constant + sum(coefficient * variable)`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Linear {
+    pub constant: i64,
+    pub coefficients: BTreeMap<String, i64>,
+}
+
+impl Expr {
+    fn constant(&self) -> Option<i64> {
+        match self {
+            Expr::Const(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// Every unknown the value depends on.
+    pub fn variables(&self) -> BTreeSet<String> {
+        let mut variables = BTreeSet::new();
+        self.collect_variables(&mut variables);
+        variables
+    }
+
+    fn collect_variables(&self, variables: &mut BTreeSet<String>) {
+        match self {
+            Expr::Const(_) => {}
+            Expr::Var(name) => {
+                variables.insert(name.clone());
+            }
+            Expr::Add(a, b) | Expr::Multiply(a, b) | Expr::LessThan(a, b) | Expr::Equals(a, b) => {
+                a.collect_variables(variables);
+                b.collect_variables(variables);
+            }
+            Expr::Load(address) => address.collect_variables(variables),
+        }
+    }
+
+    /// The value for the given unknowns, if it can be computed without overflow.
+    pub fn eval(&self, values: &BTreeMap<String, i64>) -> Option<i64> {
+        match self {
+            Expr::Const(x) => Some(*x),
+            Expr::Var(name) => values.get(name).copied(),
+            Expr::Add(a, b) => a.eval(values)?.checked_add(b.eval(values)?),
+            Expr::Multiply(a, b) => a.eval(values)?.checked_mul(b.eval(values)?),
+            Expr::LessThan(a, b) => Some((a.eval(values)? < b.eval(values)?) as i64),
+            Expr::Equals(a, b) => Some((a.eval(values)? == b.eval(values)?) as i64),
+            Expr::Load(_) => None,
+        }
+    }
+
+    /// The value as a linear combination of unknowns, if it is one.
+    pub fn linear(&self) -> Option<Linear> {
+        match self {
+            Expr::Const(x) => Some(Linear {
+                constant: *x,
+                coefficients: BTreeMap::new(),
+            }),
+            Expr::Var(name) => Some(Linear {
+                constant: 0,
+                coefficients: BTreeMap::from([(name.clone(), 1)]),
+            }),
+            Expr::Add(a, b) => {
+                let (mut a, b) = (a.linear()?, b.linear()?);
+                a.constant = a.constant.checked_add(b.constant)?;
+                for (name, coefficient) in b.coefficients {
+                    let sum = a.coefficients.entry(name).or_insert(0);
+                    *sum = sum.checked_add(coefficient)?;
+                }
+                Some(a)
+            }
+            Expr::Multiply(a, b) => {
+                let (a, b) = (a.linear()?, b.linear()?);
+                let (factor, mut term) =
+                    match (a.coefficients.is_empty(), b.coefficients.is_empty()) {
+                        (true, _) => (a.constant, b),
+                        (_, true) => (b.constant, a),
+                        _ => return None,
+                    };
+                term.constant = term.constant.checked_mul(factor)?;
+                for coefficient in term.coefficients.values_mut() {
+                    *coefficient = coefficient.checked_mul(factor)?;
+                }
+                Some(term)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Const(x) => write!(f, "{}", x),
+            Expr::Var(name) => write!(f, "{}", name),
+            Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+            Expr::Multiply(a, b) => write!(f, "({} * {})", a, b),
+            Expr::LessThan(a, b) => write!(f, "({} < {})", a, b),
+            Expr::Equals(a, b) => write!(f, "({} == {})", a, b),
+            Expr::Load(address) => write!(f, "[{}]", address),
+        }
+    }
+}
+
+/// Applies the add, multiply, less-than or equals opcode, folding constants.
+/// Returns `None` on overflow.
+fn combine(opcode: i64, a: Rc<Expr>, b: Rc<Expr>) -> Option<Rc<Expr>> {
+    if let (Some(x), Some(y)) = (a.constant(), b.constant()) {
+        let value = match opcode {
+            1 => x.checked_add(y)?,
+            2 => x.checked_mul(y)?,
+            7 => (x < y) as i64,
+            _ => (x == y) as i64,
+        };
+        return Some(Rc::new(Expr::Const(value)));
+    }
+    Some(Rc::new(match (opcode, a.constant(), b.constant()) {
+        (1, Some(0), _) | (2, Some(1), _) => return Some(b),
+        (1, _, Some(0)) | (2, _, Some(1)) => return Some(a),
+        (2, Some(0), _) | (2, _, Some(0)) => Expr::Const(0),
+        (1, ..) => Expr::Add(a, b),
+        (2, ..) => Expr::Multiply(a, b),
+        (7, ..) => Expr::LessThan(a, b),
+        _ => Expr::Equals(a, b),
+    }))
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SymbolicError {
+    Intcode(IntcodeError),
+    /// Something that has to be a number to continue depends on unknowns.
+    NotConcrete {
+        ip: usize,
+        what: &'static str,
+    },
+    StepLimit,
+}
+
+impl fmt::Display for SymbolicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SymbolicError::Intcode(error) => write!(f, "{}", error),
+            SymbolicError::NotConcrete { ip, what } => {
+                write!(f, "{} at {} depends on symbolic values", what, ip)
+            }
+            SymbolicError::StepLimit => {
+                write!(f, "gave up after {} instructions", MAX_STEPS)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SymbolicError {}
+
+impl From<IntcodeError> for SymbolicError {
+    fn from(error: IntcodeError) -> SymbolicError {
+        SymbolicError::Intcode(error)
+    }
+}
+
+/// Lets `Op::decode` see the concrete words, with unknowns read as zero.
+struct ConcreteView<'a>(&'a HashMap<usize, Rc<Expr>>);
+
+impl Memory for ConcreteView<'_> {
+    fn read(&self, address: usize) -> i64 {
+        self.0.get(&address).and_then(|x| x.constant()).unwrap_or(0)
+    }
+
+    fn write(&mut self, _: usize, _: i64) -> Result<(), OutOfMemory> {
+        Err(OutOfMemory)
+    }
+}
+
+/// Runs a program where some memory cells and inputs are unknowns, building
+/// up expressions instead of numbers. Only branch-free use of the unknowns is
+/// supported: jumps, write addresses and instructions must stay concrete.
+pub struct SymbolicMachine {
+    memory: HashMap<usize, Rc<Expr>>,
+    ip: usize,
+    relative_base: i64,
+    input: VecDeque<Rc<Expr>>,
+    output: Vec<Rc<Expr>>,
+}
+
+impl SymbolicMachine {
+    pub fn new(program: &[i64]) -> SymbolicMachine {
+        SymbolicMachine {
+            memory: program
+                .iter()
+                .enumerate()
+                .map(|(address, value)| (address, Rc::new(Expr::Const(*value))))
+                .collect(),
+            ip: 0,
+            relative_base: 0,
+            input: VecDeque::new(),
+            output: Vec::new(),
+        }
+    }
+
+    pub fn make_symbolic(&mut self, address: usize, name: &str) {
+        self.memory
+            .insert(address, Rc::new(Expr::Var(name.to_string())));
+    }
+
+    pub fn add_symbolic_input(&mut self, name: &str) {
+        self.input.push_back(Rc::new(Expr::Var(name.to_string())));
+    }
+
+    pub fn get_value(&self, address: usize) -> Rc<Expr> {
+        self.memory
+            .get(&address)
+            .cloned()
+            .unwrap_or_else(|| Rc::new(Expr::Const(0)))
+    }
+
+    pub fn get_output(&self) -> &[Rc<Expr>] {
+        &self.output
+    }
+
+    fn not_concrete(&self, what: &'static str) -> SymbolicError {
+        SymbolicError::NotConcrete { ip: self.ip, what }
+    }
+
+    fn error(&self, make: fn(usize, i64) -> IntcodeError) -> SymbolicError {
+        let instruction = ConcreteView(&self.memory).read(self.ip);
+        SymbolicError::Intcode(make(self.ip, instruction))
+    }
+
+    fn to_address(&self, address: i64) -> Result<usize, SymbolicError> {
+        usize::try_from(address).map_err(|_| {
+            let instruction = ConcreteView(&self.memory).read(self.ip);
+            SymbolicError::Intcode(IntcodeError::NegativeAddress {
+                ip: self.ip,
+                instruction,
+                address,
+            })
+        })
+    }
+
+    /// The address a parameter refers to, or the expression for it if it is unknown.
+    fn address(&self, index: usize, parameter: &Parameter) -> Result<Rc<Expr>, SymbolicError> {
+        let word = self.get_value(self.ip + index);
+        match parameter {
+            Parameter::Relative(_) => combine(1, word, Rc::new(Expr::Const(self.relative_base)))
+                .ok_or_else(|| {
+                    self.error(|ip, instruction| IntcodeError::Overflow { ip, instruction })
+                }),
+            _ => Ok(word),
+        }
+    }
+
+    fn read(&self, index: usize, parameter: &Parameter) -> Result<Rc<Expr>, SymbolicError> {
+        if let Parameter::Immediate(_) = parameter {
+            return Ok(self.get_value(self.ip + index));
+        }
+        let address = self.address(index, parameter)?;
+        match address.constant() {
+            Some(x) => Ok(self.get_value(self.to_address(x)?)),
+            None => Ok(Rc::new(Expr::Load(address))),
+        }
+    }
+
+    fn write(
+        &mut self,
+        index: usize,
+        parameter: &Parameter,
+        value: Rc<Expr>,
+    ) -> Result<(), SymbolicError> {
+        if let Parameter::Immediate(_) = parameter {
+            return Err(
+                self.error(|ip, instruction| IntcodeError::WriteToImmediate { ip, instruction })
+            );
+        }
+        let address = self.address(index, parameter)?;
+        let address = address
+            .constant()
+            .ok_or_else(|| self.not_concrete("write address"))?;
+        let address = self.to_address(address)?;
+        self.memory.insert(address, value);
+        Ok(())
+    }
+
+    fn concrete(&self, value: Rc<Expr>, what: &'static str) -> Result<i64, SymbolicError> {
+        value.constant().ok_or_else(|| self.not_concrete(what))
+    }
+
+    /// Runs until the program halts.
+    pub fn run(&mut self) -> Result<(), SymbolicError> {
+        for _ in 0..MAX_STEPS {
+            if self.get_value(self.ip).constant().is_none() {
+                return Err(self.not_concrete("instruction"));
+            }
+            let op = Op::decode(&ConcreteView(&self.memory), self.ip)?;
+            match &op {
+                Op::Add(a, b, o)
+                | Op::Multiply(a, b, o)
+                | Op::LessThan(a, b, o)
+                | Op::Equals(a, b, o) => {
+                    let value = combine(op.opcode(), self.read(1, a)?, self.read(2, b)?)
+                        .ok_or_else(|| {
+                            self.error(|ip, instruction| IntcodeError::Overflow { ip, instruction })
+                        })?;
+                    self.write(3, o, value)?;
+                }
+                Op::Input(o) => {
+                    let value = self.input.pop_front().ok_or_else(|| {
+                        self.error(|ip, instruction| IntcodeError::InputExhausted {
+                            ip,
+                            instruction,
+                        })
+                    })?;
+                    self.write(1, o, value)?;
+                }
+                Op::Output(a) => {
+                    let value = self.read(1, a)?;
+                    self.output.push(value);
+                }
+                Op::JumpIfTrue(a, b) | Op::JumpIfFalse(a, b) => {
+                    let condition = self.concrete(self.read(1, a)?, "jump condition")?;
+                    if (condition != 0) == matches!(op, Op::JumpIfTrue(..)) {
+                        let target = self.concrete(self.read(2, b)?, "jump target")?;
+                        self.ip = self.to_address(target)?;
+                        continue;
+                    }
+                }
+                Op::AdjustRelativeBase(a) => {
+                    let offset = self.concrete(self.read(1, a)?, "relative base")?;
+                    self.relative_base =
+                        self.relative_base.checked_add(offset).ok_or_else(|| {
+                            self.error(|ip, instruction| IntcodeError::Overflow { ip, instruction })
+                        })?;
+                }
+                Op::Halt => return Ok(()),
+            }
+            self.ip += op.size();
+        }
+        Err(SymbolicError::StepLimit)
+    }
+}
+
+/// Calls `f` with every combination of values from `domains` until it returns
+/// something.
+fn search<T>(
+    domains: &[(&str, RangeInclusive<i64>)],
+    values: &mut BTreeMap<String, i64>,
+    f: &mut impl FnMut(&BTreeMap<String, i64>) -> Option<T>,
+) -> Option<T> {
+    let ((name, range), rest) = match domains.split_first() {
+        Some(first) => first,
+        None => return f(values),
+    };
+    for value in range.clone() {
+        values.insert(name.to_string(), value);
+        if let Some(found) = search(rest, values, f) {
+            return Some(found);
+        }
+    }
+    values.remove(*name);
+    None
+}
+
+/// Finds values for the unknowns, each within its domain, that make `expr`
+/// equal to `target`. When `expr` is linear the last unknown with a non-zero
+/// coefficient is solved for directly, so only the other domains are searched.
+pub fn solve(
+    expr: &Expr,
+    target: i64,
+    domains: &[(&str, RangeInclusive<i64>)],
+) -> Option<BTreeMap<String, i64>> {
+    let linear = expr.linear();
+    let solved = linear.as_ref().and_then(|linear| {
+        domains
+            .iter()
+            .rposition(|(name, _)| linear.coefficients.get(*name).is_some_and(|x| *x != 0))
+    });
+    let (linear, solved) = match (linear, solved) {
+        (Some(linear), Some(solved)) => (linear, solved),
+        _ => {
+            return search(domains, &mut BTreeMap::new(), &mut |values| {
+                Some(values.clone()).filter(|_| expr.eval(values) == Some(target))
+            })
+        }
+    };
+    let (name, range) = &domains[solved];
+    let coefficient = linear.coefficients[*name];
+    let mut others = domains.to_vec();
+    others.remove(solved);
+    search(&others, &mut BTreeMap::new(), &mut |values| {
+        let mut rest = target.checked_sub(linear.constant)?;
+        for (other, factor) in linear.coefficients.iter() {
+            if other != name {
+                rest = rest.checked_sub(factor.checked_mul(*values.get(other)?)?)?;
+            }
+        }
+        if rest % coefficient != 0 || !range.contains(&(rest / coefficient)) {
+            return None;
+        }
+        let mut values = values.clone();
+        values.insert(name.to_string(), rest / coefficient);
+        Some(values)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input_files::read_content;
+    use crate::intcode::symbolic::solve;
+    use crate::intcode::symbolic::SymbolicError;
+    use crate::intcode::symbolic::SymbolicMachine;
+    use crate::intcode::IntCodeComputer;
+    use std::collections::BTreeMap;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_day02_expression() {
+        let content = read_content("data/day02.txt").unwrap();
+        let program = IntCodeComputer::read_program(&content).get_memory();
+        let mut m = SymbolicMachine::new(&program);
+        m.make_symbolic(1, "noun");
+        m.make_symbolic(2, "verb");
+        m.run().unwrap();
+        let result = m.get_value(0);
+        let linear = result.linear().unwrap();
+        assert_eq!(
+            linear.coefficients.keys().collect::<Vec<&String>>(),
+            vec!["noun", "verb"]
+        );
+        let values = BTreeMap::from([("noun".to_string(), 12), ("verb".to_string(), 2)]);
+        assert_eq!(result.eval(&values), Some(3850704));
+        let answer = solve(&result, 19690720, &[("noun", 0..=99), ("verb", 0..=99)]).unwrap();
+        assert_eq!(answer["noun"] * 100 + answer["verb"], 6718);
+    }
+
+    #[test]
+    fn test_dependencies_and_nonlinear_solve() {
+        // Outputs a < b and a * b
+        let mut m =
+            SymbolicMachine::new(&[3, 20, 3, 21, 7, 20, 21, 22, 4, 22, 2, 20, 21, 23, 4, 23, 99]);
+        m.add_symbolic_input("a");
+        m.add_symbolic_input("b");
+        m.run().unwrap();
+        let output = m.get_output();
+        assert_eq!(output[0].to_string(), "(a < b)");
+        assert_eq!(output[1].to_string(), "(a * b)");
+        assert_eq!(
+            output[1].variables(),
+            BTreeSet::from(["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(output[1].linear(), None);
+        let answer = solve(&output[1], 12, &[("a", 5..=9), ("b", 0..=9)]).unwrap();
+        assert_eq!((answer["a"], answer["b"]), (6, 2));
+    }
+
+    #[test]
+    fn test_symbolic_branch() {
+        let mut m = SymbolicMachine::new(&[3, 9, 1005, 9, 0, 99]);
+        m.add_symbolic_input("x");
+        assert_eq!(
+            m.run(),
+            Err(SymbolicError::NotConcrete {
+                ip: 2,
+                what: "jump condition"
+            })
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use crate::intcode::Op;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+/// One executed instruction.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TraceEvent {
+    pub ip: usize,
+    pub op: Op,
+    /// Values of the parameters the instruction reads, in order.
+    pub operands: Vec<i64>,
+    /// Address and value written, if the instruction wrote to memory.
+    pub write: Option<(usize, i64)>,
+    pub relative_base: i64,
+}
+
+impl TraceEvent {
+    /// The address of the next instruction if this one jumped somewhere other
+    /// than the following instruction.
+    pub fn jump_target(&self) -> Option<usize> {
+        let taken = match self.op {
+            Op::JumpIfTrue(..) => self.operands[0] != 0,
+            Op::JumpIfFalse(..) => self.operands[0] == 0,
+            _ => false,
+        };
+        if taken {
+            usize::try_from(self.operands[1]).ok()
+        } else {
+            None
+        }
+    }
+
+    pub fn to_json(&self, step: u64) -> String {
+        let operands: Vec<String> = self.operands.iter().map(|x| x.to_string()).collect();
+        let write = match self.write {
+            Some((address, value)) => format!("{{\"address\":{},\"value\":{}}}", address, value),
+            None => "null".to_string(),
+        };
+        format!(
+            "{{\"step\":{},\"ip\":{},\"op\":\"{}\",\"operands\":[{}],\"write\":{},\"relative_base\":{}}}",
+            step,
+            self.ip,
+            self.op,
+            operands.join(","),
+            write,
+            self.relative_base
+        )
+    }
+}
+
+/// Receives every instruction a machine executes.
+pub trait Tracer: Send {
+    fn trace(&mut self, event: &TraceEvent);
+
+    /// Writes out anything buffered, once the run is over.
+    fn flush(&mut self) {}
+
+    /// The first error the tracer ran into. The machine faults once there is
+    /// one.
+    fn error(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Lets the caller keep a handle to a tracer that is attached to a machine.
+impl<T: Tracer> Tracer for Arc<Mutex<T>> {
+    fn trace(&mut self, event: &TraceEvent) {
+        self.lock().unwrap().trace(event);
+    }
+
+    fn flush(&mut self) {
+        self.lock().unwrap().flush();
+    }
+
+    fn error(&self) -> Option<String> {
+        self.lock().unwrap().error()
+    }
+}
+
+/// Sends every event to both tracers.
+impl<A: Tracer, B: Tracer> Tracer for (A, B) {
+    fn trace(&mut self, event: &TraceEvent) {
+        self.0.trace(event);
+        self.1.trace(event);
+    }
+
+    fn flush(&mut self) {
+        self.0.flush();
+        self.1.flush();
+    }
+
+    fn error(&self) -> Option<String> {
+        self.0.error().or_else(|| self.1.error())
+    }
+}
+
+/// The tracer attached to a machine, if any. Cloned machines start without a
+/// tracer, and tracers are ignored when machines are compared.
+#[derive(Default)]
+pub struct TracerHook(pub Option<Box<dyn Tracer>>);
+
+impl Clone for TracerHook {
+    fn clone(&self) -> TracerHook {
+        TracerHook(None)
+    }
+}
+
+impl PartialEq for TracerHook {
+    fn eq(&self, _: &TracerHook) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for TracerHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "TracerHook(Some(..))"),
+            None => write!(f, "TracerHook(None)"),
+        }
+    }
+}
+
+/// Writes one JSON object per executed instruction. Writing stops at the
+/// first error, which is kept for the machine to report.
+pub struct JsonlTracer<W: Write + Send> {
+    writer: W,
+    step: u64,
+    error: Option<io::Error>,
+}
+
+impl<W: Write + Send> JsonlTracer<W> {
+    pub fn new(writer: W) -> JsonlTracer<W> {
+        JsonlTracer {
+            writer,
+            step: 0,
+            error: None,
+        }
+    }
+}
+
+impl<W: Write + Send> Tracer for JsonlTracer<W> {
+    fn trace(&mut self, event: &TraceEvent) {
+        if self.error.is_none() {
+            self.error = writeln!(self.writer, "{}", event.to_json(self.step)).err();
+        }
+        self.step += 1;
+    }
+
+    fn flush(&mut self) {
+        if self.error.is_none() {
+            self.error = self.writer.flush().err();
+        }
+    }
+
+    fn error(&self) -> Option<String> {
+        self.error.as_ref().map(|x| x.to_string())
+    }
+}
+
+/// Number of entries shown in each section of the profile report.
+const REPORT_ROWS: usize = 10;
+
+#[derive(Debug, Default)]
+pub struct Profiler {
+    cycles: u64,
+    hits: HashMap<usize, u64>,
+    ops: HashMap<usize, Op>,
+    /// Taken jumps back to an earlier address, by (target, source).
+    back_edges: HashMap<(usize, usize), u64>,
+    last_input: Option<u64>,
+    cycles_between_inputs: Vec<u64>,
+}
+
+impl Tracer for Profiler {
+    fn trace(&mut self, event: &TraceEvent) {
+        *self.hits.entry(event.ip).or_insert(0) += 1;
+        self.ops.insert(event.ip, event.op);
+        if let Some(target) = event.jump_target().filter(|x| *x <= event.ip) {
+            *self.back_edges.entry((target, event.ip)).or_insert(0) += 1;
+        }
+        if let Op::Input(_) = event.op {
+            if let Some(last) = self.last_input {
+                self.cycles_between_inputs.push(self.cycles - last);
+            }
+            self.last_input = Some(self.cycles);
+        }
+        self.cycles += 1;
+    }
+}
+
+impl Profiler {
+    /// Loops as (start, end, iterations, instructions executed inside), most
+    /// expensive first.
+    pub fn hot_loops(&self) -> Vec<(usize, usize, u64, u64)> {
+        let mut loops: Vec<(usize, usize, u64, u64)> = self
+            .back_edges
+            .iter()
+            .map(|((start, end), iterations)| {
+                let inside = self
+                    .hits
+                    .iter()
+                    .filter(|(address, _)| (*start..=*end).contains(*address))
+                    .map(|(_, hits)| hits)
+                    .sum();
+                (*start, *end, *iterations, inside)
+            })
+            .collect();
+        loops.sort_by(|a, b| b.3.cmp(&a.3).then(a.0.cmp(&b.0)));
+        loops
+    }
+
+    pub fn render(&self) -> String {
+        let mut output = format!("Executed {} instructions\n", self.cycles);
+
+        let mut hits: Vec<(&usize, &u64)> = self.hits.iter().collect();
+        hits.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        output.push_str("Hottest instructions:\n");
+        for (address, count) in hits.iter().take(REPORT_ROWS) {
+            output.push_str(&format!(
+                "{:>7}: {:>10}  {}\n",
+                address, count, self.ops[address]
+            ));
+        }
+
+        output.push_str("Hot loops:\n");
+        for (start, end, iterations, inside) in self.hot_loops().iter().take(REPORT_ROWS) {
+            output.push_str(&format!(
+                "{:>7}-{}: {} iterations, {} instructions\n",
+                start, end, iterations, inside
+            ));
+        }
+
+        let gaps = &self.cycles_between_inputs;
+        if !gaps.is_empty() {
+            let mut sorted = gaps.clone();
+            sorted.sort();
+            output.push_str(&format!(
+                "Cycles between inputs: {} gaps, min {}, median {}, max {}\n",
+                gaps.len(),
+                sorted[0],
+                sorted[sorted.len() / 2],
+                sorted[sorted.len() - 1]
+            ));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::trace::JsonlTracer;
+    use crate::intcode::trace::Profiler;
+    use crate::intcode::IntCodeComputer;
+    use crate::intcode::IntcodeError;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    /// Reads a count and outputs count, count - 1, ..., 1.
+    const COUNTDOWN: &str = "3,20,4,20,1001,20,-1,20,1005,20,2,99";
+
+    #[test]
+    fn test_jsonl_trace() {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        struct Shared(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for Shared {
+            fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(data)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut m = IntCodeComputer::read_program_with_input(COUNTDOWN, 1);
+        m.set_tracer(Box::new(JsonlTracer::new(Shared(buffer.clone()))));
+        m.execute_until_halted().unwrap();
+        let text = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[0],
+            "{\"step\":0,\"ip\":0,\"op\":\"IN [20]\",\"operands\":[],\"write\":{\"address\":20,\"value\":1},\"relative_base\":0}"
+        );
+        assert_eq!(
+            lines[2],
+            "{\"step\":2,\"ip\":4,\"op\":\"ADD [20], #-1, [20]\",\"operands\":[1,-1],\"write\":{\"address\":20,\"value\":0},\"relative_base\":0}"
+        );
+        assert!(lines[4].contains("\"op\":\"HLT\""));
+    }
+
+    #[test]
+    fn test_trace_write_error() {
+        /// Takes this many more bytes, then fails like a pipe whose reader has
+        /// gone away.
+        struct Closed(usize);
+        impl std::io::Write for Closed {
+            fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+                if self.0 < data.len() {
+                    return Err(std::io::ErrorKind::BrokenPipe.into());
+                }
+                self.0 -= data.len();
+                Ok(data.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut m = IntCodeComputer::read_program_with_input(COUNTDOWN, 3);
+        m.set_tracer(Box::new(JsonlTracer::new(Closed(0))));
+        assert_eq!(
+            m.execute_until_halted(),
+            Err(IntcodeError::TraceFailed {
+                ip: 0,
+                message: "broken pipe".to_string()
+            })
+        );
+        assert!(m.execute_until_stopped().is_err());
+
+        let mut m = IntCodeComputer::read_program_with_input(COUNTDOWN, 3);
+        let buffered = std::io::BufWriter::with_capacity(10_000, Closed(200));
+        m.set_tracer(Box::new(JsonlTracer::new(buffered)));
+        m.execute_until_halted().unwrap();
+        assert_eq!(
+            m.finish_trace().unwrap_err().to_string(),
+            "could not write trace at 11: broken pipe"
+        );
+    }
+
+    #[test]
+    fn test_profiler() {
+        let profiler = Arc::new(Mutex::new(Profiler::default()));
+        let mut m = IntCodeComputer::read_program_with_input(COUNTDOWN, 3);
+        m.set_tracer(Box::new(profiler.clone()));
+        m.execute_until_halted().unwrap();
+        assert_eq!(m.take_output(), vec![3, 2, 1]);
+        let profiler = profiler.lock().unwrap();
+        assert_eq!(profiler.cycles, 11);
+        assert_eq!(profiler.hits[&2], 3);
+        assert_eq!(profiler.hot_loops(), vec![(2, 8, 2, 9)]);
+        assert!(profiler
+            .render()
+            .contains("      2-8: 2 iterations, 9 instructions"));
+    }
+
+    #[test]
+    fn test_cycles_between_inputs() {
+        let profiler = Arc::new(Mutex::new(Profiler::default()));
+        let mut m = IntCodeComputer::read_program("3,0,1101,1,1,9,3,0,99,0");
+        m.set_tracer(Box::new(profiler.clone()));
+        m.add_input(5);
+        m.add_input(6);
+        m.execute_until_halted().unwrap();
+        assert_eq!(profiler.lock().unwrap().cycles_between_inputs, vec![2]);
+    }
+}
@@ -0,0 +1,6 @@
+//! The Intcode machine and the input handling it relies on, as a library so
+//! that the benchmarks can link against it.
+
+pub mod gzip;
+pub mod input_files;
+pub mod intcode;
@@ -1,58 +1,688 @@
+use aoc2019::gzip;
+use aoc2019::input_files;
+use aoc2019::intcode;
 use std::env;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
 
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod input_files;
-mod intcode;
+mod answers;
 mod position;
 mod position3;
+mod report;
+mod solution;
+mod timing;
 
-fn execute_day(day: usize) {
-    println!("Day {}", day);
-    match day {
-        1 => day01::execute(),
-        2 => day02::execute(),
-        3 => day03::execute(),
-        4 => day04::execute(),
-        5 => day05::execute(),
-        6 => day06::execute(),
-        7 => day07::execute(),
-        8 => day08::execute(),
-        9 => day09::execute(),
-        10 => day10::execute(),
-        11 => day11::execute(),
-        12 => day12::execute(),
-        13 => day13::execute(),
-        14 => day14::execute(),
-        15 => day15::execute(),
-        _ => panic!("Day {} not implemented", day),
-    }
-    println!("");
+/// Declares the day modules and registers each one's `Solver`, so a day
+/// cannot be added without also being run.
+macro_rules! days {
+    ($($day:ident),* $(,)?) => {
+        $(mod $day;)*
+
+        /// Every day's solution, in order.
+        fn solutions() -> Vec<Box<dyn solution::AnySolution>> {
+            vec![$(Box::new($day::Solver)),*]
+        }
+    };
+}
+
+days!(
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15,
+);
+
+/// Runs both parts on the day's own input, or on `input` if given.
+fn execute_day(solution: &dyn solution::AnySolution, input: Option<&String>) -> report::DayReport {
+    let (input, read_time) = timing::timed(|| read_file(input.unwrap_or(&solution.input_path())));
+    let (parsed, parse_time) = timing::timed(|| solution.parse(&input));
+    let parts = (1..=2)
+        .map(|part| timing::timed(|| solution.solve(part, &parsed)))
+        .collect();
+    report::DayReport {
+        day: solution.day(),
+        title: solution.title().to_string(),
+        read_time,
+        parse_time,
+        parts,
+    }
+}
+
+/// The day runner's `--format`. Subcommands such as `convert` have their own.
+fn report_format(args: &[String]) -> report::Format {
+    option_value(args, "--format").map_or(report::Format::Text, |name| {
+        report::Format::from_name(name).expect("Unknown format, use text or json")
+    })
+}
+
+/// Prints a day as text, with the times if `time` is set, or as JSON.
+fn print_day(report: &report::DayReport, format: report::Format, time: bool) {
+    match format {
+        report::Format::Text => println!("{}", report.to_text(time)),
+        report::Format::Json => println!("{}", report.to_json()),
+    }
+}
+
+/// The file given with `--input`, which is only accepted when exactly one day
+/// is selected, as the other days would not know what to make of it.
+fn single_day_input<'a>(args: &'a [String], days: &[usize]) -> Option<&'a String> {
+    let input = option_value(args, "--input")?;
+    if days.len() != 1 {
+        eprintln!("--input needs exactly one day, as in '<day> --input <path>'");
+        std::process::exit(1);
+    }
+    Some(input)
+}
+
+/// Runs every part of every day, or of the days given, `--runs` times and
+/// reports the spread of the times.
+fn bench(args: &[String]) {
+    let runs = option_value(args, "--runs")
+        .map_or(10, |x| x.parse::<usize>().expect("Invalid number of runs"))
+        .max(1);
+    let days: Vec<usize> = positional_args(args, &["--runs", "--input"])
+        .into_iter()
+        .map(|x| {
+            x.parse::<usize>()
+                .expect("Usage: bench [day...] [--runs n] [--input <path>]")
+        })
+        .collect();
+    let input = single_day_input(args, &days);
+    println!("{} runs per part", runs);
+    println!("Day  Part         min      median         p95");
+    for solution in solutions() {
+        if !days.is_empty() && !days.contains(&solution.day()) {
+            continue;
+        }
+        let parsed = solution.parse(&read_file(input.unwrap_or(&solution.input_path())));
+        for part in 1..=2 {
+            let samples: Vec<std::time::Duration> = (0..runs)
+                .map(|_| timing::timed(|| solution.solve(part, &parsed)).1)
+                .collect();
+            let stats = timing::Stats::from_samples(&samples).unwrap();
+            println!(
+                "{:>3}  {:>4}  {:>10.1?}  {:>10.1?}  {:>10.1?}",
+                solution.day(),
+                part,
+                stats.min,
+                stats.median,
+                stats.p95
+            );
+        }
+    }
+}
+
+/// Reads a file named on the command line, or stdin for `-`, exiting with the
+/// reason if it cannot be read.
+fn read_file(filename: &str) -> String {
+    match input_files::read_content(filename) {
+        Ok(content) => content,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Like `read_file`, for files that need not be text.
+fn read_file_bytes(filename: &str) -> Vec<u8> {
+    match input_files::read_bytes(filename) {
+        Ok(data) => data,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Runs `f`, turning a panic into an error so one broken day does not stop
+/// the others from being checked.
+fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|error| {
+        error
+            .downcast_ref::<&str>()
+            .map(|x| x.to_string())
+            .or_else(|| error.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panicked".to_string())
+    })
+}
+
+/// Checks every day, or the days given, against the recorded answers and
+/// exits with an error if any part is wrong.
+fn verify(args: &[String]) {
+    let days: Vec<usize> = positional_args(args, &["--answers", "--input"])
+        .into_iter()
+        .map(|x| x.parse::<usize>().expect("Invalid day"))
+        .collect();
+    let input = single_day_input(args, &days);
+    let path = option_value(args, "--answers").map_or("answers.toml", |x| x.as_str());
+    let answers = match answers::Answers::parse(&read_file(path)) {
+        Ok(answers) => answers,
+        Err(error) => {
+            eprintln!("{}: {}", path, error);
+            std::process::exit(1);
+        }
+    };
+    let mut failures = Vec::new();
+    let mut checked = 0;
+    println!("Day  Part  Result   Answer");
+    for solution in solutions() {
+        if !days.is_empty() && !days.contains(&solution.day()) {
+            continue;
+        }
+        let path = input.unwrap_or(&solution.input_path()).to_string();
+        let parsed = catch_panic(|| solution.parse(&read_file(&path)));
+        for part in 1..=2 {
+            checked += 1;
+            let actual = match &parsed {
+                Ok(parsed) => catch_panic(|| solution.solve(part, parsed)),
+                Err(message) => Err(message.clone()),
+            };
+            let expected = answers.get(solution.day(), part);
+            let (result, shown) = match (&actual, expected) {
+                (Err(message), _) => ("panic", message.clone()),
+                (Ok(actual), None) => ("missing", actual.to_string()),
+                (Ok(actual), Some(expected)) if answers::matches(expected, actual) => {
+                    ("ok", actual.to_string())
+                }
+                (Ok(actual), Some(_)) => ("FAIL", actual.to_string()),
+            };
+            let summary = match shown.contains('\n') {
+                true => "(image)".to_string(),
+                false => shown,
+            };
+            println!(
+                "{:>3}  {:>4}  {:<7}  {}",
+                solution.day(),
+                part,
+                result,
+                summary
+            );
+            if result != "ok" {
+                failures.push((solution.day(), part, expected.cloned(), actual));
+            }
+        }
+    }
+    for (day, part, expected, actual) in failures.iter() {
+        println!("\nDay {} part {}:", day, part);
+        match expected {
+            Some(expected) => println!("  expected:{}{}", expected.separator(), expected),
+            None => println!("  expected: nothing recorded in {}", path),
+        }
+        match actual {
+            Ok(actual) => println!("  actual:{}{}", actual.separator(), actual),
+            Err(message) => println!("  actual: panicked with {}", message),
+        }
+    }
+    println!(
+        "\n{} of {} answers correct",
+        checked - failures.len(),
+        checked
+    );
+    if !failures.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+fn disassemble(filename: &str) {
+    let content = read_file(filename);
+    let m = intcode::IntCodeComputer::read_program(&content);
+    print!(
+        "{}",
+        intcode::disassembler::Listing::new(&m.get_memory()).render()
+    );
+}
+
+/// Prints the control-flow graph in Graphviz DOT format, with a summary on stderr.
+fn cfg(filename: &str) {
+    let content = read_file(filename);
+    let m = intcode::IntCodeComputer::read_program(&content);
+    let graph = intcode::analysis::ControlFlowGraph::new(&m.get_memory());
+    print!("{}", graph.to_dot());
+    eprint!("{}", graph.render_summary());
+}
+
+fn assemble(filename: &String) {
+    let source = read_file(filename);
+    match intcode::assembler::assemble(&source) {
+        Ok(words) => println!("{}", intcode::assembler::to_program_text(&words)),
+        Err(error) => {
+            eprintln!("{}: {}", filename, error);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Reads a program image in any format, or a snapshot written by `run --save`
+/// or the debugger. Either may be gzip compressed.
+fn load_machine(filename: &String) -> intcode::IntCodeComputer {
+    let data = read_file_bytes(filename);
+    let loaded = match gzip::is_gzip(&data) {
+        true => gzip::decompress(&data).map_err(|error| error.to_string()),
+        false => Ok(data),
+    }
+    .and_then(|data| match std::str::from_utf8(&data) {
+        Ok(text) if intcode::snapshot::is_snapshot(text) => {
+            intcode::IntCodeComputer::from_snapshot(text).map_err(|error| error.to_string())
+        }
+        _ => intcode::image::load(&data)
+            .map(|image| intcode::IntCodeComputer::from_image(&image))
+            .map_err(|error| error.to_string()),
+    });
+    match loaded {
+        Ok(m) => m,
+        Err(error) => {
+            eprintln!("{}: {}", filename, error);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Writes `data` to `path`, compressed if the name ends in `.gz`.
+fn write_file(path: &str, data: &[u8]) {
+    let data = match path.ends_with(".gz") {
+        true => gzip::compress(data),
+        false => data.to_vec(),
+    };
+    std::fs::write(path, data).expect("Should have been able to write file");
+}
+
+/// Converts a program image between formats. The output format is taken from
+/// `--format` or the output file name.
+fn convert(args: &[String]) {
+    let usage = "Usage: convert <input> <output> [--format text|binary|gzip]";
+    let (input, output) = (args.first().expect(usage), args.get(1).expect(usage));
+    let format = match option_value(args, "--format") {
+        Some(name) => intcode::image::Format::from_name(name).expect("Unknown format"),
+        None => intcode::image::Format::from_path(output),
+    };
+    let data = read_file_bytes(input);
+    let result = intcode::image::load(&data).and_then(|image| intcode::image::save(&image, format));
+    match result {
+        Ok(data) => std::fs::write(output, data).expect("Should have been able to write file"),
+        Err(error) => {
+            eprintln!("{}: {}", input, error);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn debug(filename: &String) {
+    let m = load_machine(filename);
+    intcode::debugger::Debugger::new(m).run_interactive();
+}
+
+/// Runs an ASCII program, such as days 17, 21 and 25, as a line based console.
+fn ascii(filename: &String) {
+    let m = load_machine(filename);
+    let stdin = std::io::stdin();
+    let result =
+        intcode::ascii::AsciiConsole::new(m).run_interactive(stdin.lock(), std::io::stdout());
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
+
+/// The value following `--name` on the command line, if the option is given.
+fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a String> {
+    let index = args.iter().position(|x| x == name)?;
+    Some(
+        args.get(index + 1)
+            .unwrap_or_else(|| panic!("Missing value for {}", name)),
+    )
+}
+
+/// The arguments that are not options, skipping the value that follows each
+/// option in `with_values`.
+fn positional_args<'a>(args: &'a [String], with_values: &[&str]) -> Vec<&'a String> {
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if with_values.contains(&arg.as_str()) {
+            args.next();
+        } else if !arg.starts_with("--") {
+            positional.push(arg);
+        }
+    }
+    positional
+}
+
+fn run(args: &[String]) {
+    let filename = args.first().expect(
+        "Usage: run <program|snapshot> [--patch <name>,...] [--patch-file <file>] [--save <snapshot>] [--dump <image>] [--engine <name>] [--extensions <opcode>=<name>,...] [--memory-limit <words>] [--trace <file.jsonl>] [--profile] [--max-cycles <n>] [--timeout <ms>]",
+    );
+    let save_to = option_value(args, "--save");
+    let mut m = load_machine(filename);
+    let mut patches = Vec::new();
+    if let Some(names) = option_value(args, "--patch") {
+        let set = intcode::patch::PatchSet::for_program(filename);
+        for name in names.split(',') {
+            patches.push(set.clone().and_then(|x| x.get(name).cloned()));
+        }
+    }
+    if let Some(path) = option_value(args, "--patch-file") {
+        patches.push(intcode::patch::Patch::parse(&read_file(path)));
+    }
+    for patch in patches {
+        if let Err(error) = patch.and_then(|x| m.apply_patch(&x)) {
+            eprintln!("Could not patch {}: {}", filename, error);
+            std::process::exit(1);
+        }
+    }
+    if let Some(name) = option_value(args, "--engine") {
+        m.set_engine(intcode::engine::Engine::from_name(name).expect("Unknown engine"));
+    }
+    if let Some(list) = option_value(args, "--extensions") {
+        for entry in list.split(',') {
+            let (opcode, name) = entry
+                .split_once('=')
+                .expect("Extensions are given as <opcode>=<print|trap|yield|jump>");
+            let builtin = intcode::extension::Builtin::from_name(name).expect("Unknown extension");
+            let opcode = opcode.parse::<i64>().expect("Invalid opcode");
+            if let Err(error) = builtin.register(&mut m, opcode) {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(words) = option_value(args, "--memory-limit") {
+        m.set_memory_limit(Some(words.parse::<usize>().expect("Invalid memory limit")));
+    }
+    let profiler = Arc::new(Mutex::new(intcode::trace::Profiler::default()));
+    let profile = args.iter().any(|x| x == "--profile");
+    let trace = option_value(args, "--trace").map(|path| {
+        let file = std::fs::File::create(path).expect("Should have been able to create trace");
+        intcode::trace::JsonlTracer::new(std::io::BufWriter::new(file))
+    });
+    match (trace, profile) {
+        (Some(trace), true) => m.set_tracer(Box::new((trace, profiler.clone()))),
+        (Some(trace), false) => m.set_tracer(Box::new(trace)),
+        (None, true) => m.set_tracer(Box::new(profiler.clone())),
+        (None, false) => {}
+    }
+    let mut budget = match option_value(args, "--max-cycles") {
+        Some(cycles) => {
+            intcode::Budget::cycles(cycles.parse::<u64>().expect("Invalid cycle count"))
+        }
+        None => intcode::Budget::default(),
+    };
+    if let Some(timeout) = option_value(args, "--timeout") {
+        let milliseconds = timeout.parse::<u64>().expect("Invalid timeout");
+        budget = budget.with_timeout(std::time::Duration::from_millis(milliseconds));
+    }
+    let mut result = Ok(intcode::Outcome::Yielded);
+    while result == Ok(intcode::Outcome::Yielded) {
+        result = m.execute_with_budget(
+            &mut intcode::channels::StdinInput,
+            &mut intcode::channels::StdoutOutput,
+            budget,
+        );
+    }
+    // Flushes the trace file before any early exit
+    let result = result.and_then(|outcome| m.finish_trace().map(|_| outcome));
+    drop(m.take_tracer());
+    if profile {
+        eprint!("{}", profiler.lock().unwrap().render());
+    }
+    let outcome = match result {
+        Ok(outcome) => outcome,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    if let Some(path) = option_value(args, "--dump") {
+        let format = intcode::image::Format::from_path(path);
+        match intcode::image::save(&m.to_image(), format) {
+            Ok(data) => std::fs::write(path, data).expect("Should have been able to write dump"),
+            Err(error) => eprintln!("Could not dump memory: {}", error),
+        }
+    }
+    if let (false, Some(path)) = (m.has_terminated(), save_to) {
+        write_file(path, m.to_snapshot().as_bytes());
+        eprintln!("Saved snapshot to {}", path);
+    } else if outcome == intcode::Outcome::BudgetExhausted {
+        eprintln!("Stopped after running out of cycles or time");
+        std::process::exit(1);
+    } else if outcome == intcode::Outcome::Waiting {
+        eprintln!("Input ended while the program was waiting for more");
+        std::process::exit(1);
+    }
+}
+
+/// Prints the memory cells that differ between two programs or snapshots, or
+/// that a run of one program changes, in the patch file format.
+fn diff(args: &[String]) {
+    let usage = "Usage: diff <before> <after> | diff <program> --run [input...]";
+    let before = load_machine(args.first().expect(usage));
+    let after = match args.get(1).expect(usage).as_str() {
+        "--run" => {
+            let mut m = before.clone();
+            for input in args[2..].iter() {
+                m.add_input(input.parse::<i64>().expect("Invalid input"));
+            }
+            if let Err(error) = m.execute_until_stopped() {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+            m
+        }
+        other => load_machine(&other.to_string()),
+    };
+    print!(
+        "{}",
+        intcode::patch::Patch::diff(&before.get_memory(), &after.get_memory())
+    );
+}
+
+/// Runs a day 23 style packet network: prints the first Y value sent to the
+/// NAT and the first Y value the NAT delivers twice in a row.
+fn network(args: &[String]) {
+    let filename = args
+        .first()
+        .expect("Usage: network <program> [nodes] [--threads]");
+    let size = match args.get(1).filter(|x| *x != "--threads") {
+        Some(x) => x.parse::<usize>().unwrap(),
+        None => 50,
+    };
+    let scheduler = if args.iter().any(|x| x == "--threads") {
+        intcode::network::Scheduler::ThreadPerMachine
+    } else {
+        intcode::network::Scheduler::RoundRobin
+    };
+    let program = intcode::IntCodeComputer::read_program(&read_file(filename));
+    let mut network = intcode::network::IntcodeNetwork::packet_switched(&program, size).with_nat();
+    let status = network.run(scheduler, |traffic| {
+        let sent = &traffic.nat_sent;
+        sent.len() >= 2 && sent[sent.len() - 1].y == sent[sent.len() - 2].y
+    });
+    let traffic = network.get_traffic();
+    match traffic.nat_received.first() {
+        Some(packet) => println!("First Y sent to the NAT: {}", packet.y),
+        None => println!("Nothing was sent to the NAT"),
+    }
+    match status {
+        Ok(intcode::network::NetworkStatus::Stopped) => println!(
+            "First Y delivered twice in a row by the NAT: {}",
+            traffic.nat_sent.last().unwrap().y
+        ),
+        Ok(status) => println!("Network stopped: {:?}", status),
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Runs a program with the given input values and prints its disassembly
+/// marked with which instructions ran and which branches went both ways.
+fn coverage(args: &[String]) {
+    let filename = args.first().expect("Usage: coverage <program> [input...]");
+    let content = read_file(filename);
+    let mut m = intcode::IntCodeComputer::read_program(&content);
+    for value in args[1..].iter() {
+        m.add_input(value.parse::<i64>().expect("Invalid input value"));
+    }
+    let coverage = Arc::new(Mutex::new(intcode::coverage::Coverage::default()));
+    m.set_tracer(Box::new(coverage.clone()));
+    let result = m.execute_until_halted();
+    print!("{}", coverage.lock().unwrap().render(&m.get_memory()));
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
+
+/// Runs a program with the given memory cells, and optionally its first inputs,
+/// as unknowns and prints what memory cell 0 and each output are in terms of
+/// them. With `--target`, also
+/// finds values between 0 and 99 that make cell 0 equal to the target.
+fn symbolic(args: &[String]) {
+    let usage = "Usage: symbolic <program> <address>... [--inputs <count>] [--target <value>]";
+    let filename = args.first().expect(usage);
+    let target = option_value(args, "--target").map(|x| x.parse::<i64>().expect(usage));
+    let names: Vec<(usize, String)> = args[1..]
+        .iter()
+        .take_while(|x| !x.starts_with("--"))
+        .map(|x| (x.parse::<usize>().expect(usage), format!("m{}", x)))
+        .collect();
+    let content = read_file(filename);
+    let mut m = intcode::symbolic::SymbolicMachine::new(
+        &intcode::IntCodeComputer::read_program(&content).get_memory(),
+    );
+    for (address, name) in names.iter() {
+        m.make_symbolic(*address, name);
+    }
+    let inputs = option_value(args, "--inputs").map_or(0, |x| x.parse::<usize>().expect(usage));
+    for index in 0..inputs {
+        m.add_symbolic_input(&format!("in{}", index));
+    }
+    if let Err(error) = m.run() {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+    let describe = |expr: &intcode::symbolic::Expr| {
+        let variables: Vec<String> = expr.variables().into_iter().collect();
+        format!("{} (depends on: {})", expr, variables.join(", "))
+    };
+    println!("[0] = {}", describe(&m.get_value(0)));
+    for (index, value) in m.get_output().iter().enumerate() {
+        println!("output {} = {}", index, describe(value));
+    }
+    if let Some(target) = target {
+        let domains: Vec<(&str, std::ops::RangeInclusive<i64>)> = names
+            .iter()
+            .map(|(_, name)| (name.as_str(), 0..=99))
+            .collect();
+        match intcode::symbolic::solve(&m.get_value(0), target, &domains) {
+            Some(values) => {
+                for (name, value) in values {
+                    println!("{} = {}", name, value);
+                }
+            }
+            None => {
+                println!("No solution");
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Checks random programs for panics and engine disagreements. With `--save`,
+/// the shrunk failure is appended to the given file, which should be
+/// data/fuzz_regressions.txt for the tests to check it.
+fn fuzz(args: &[String]) {
+    let iterations = match args.first().filter(|x| !x.starts_with("--")) {
+        Some(x) => x.parse::<usize>().expect("Invalid iteration count"),
+        None => 10_000,
+    };
+    let seed = match option_value(args, "--seed") {
+        Some(x) => x.parse::<u64>().expect("Invalid seed"),
+        None => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    };
+    println!("Fuzzing {} programs with seed {}", iterations, seed);
+    let failure = match intcode::fuzz::fuzz(seed, iterations) {
+        Some(failure) => failure,
+        None => {
+            println!("No problems found");
+            return;
+        }
+    };
+    println!("{}", failure.problem);
+    let case = failure.to_regression_case();
+    print!("{}", case);
+    if let Some(path) = option_value(args, "--save") {
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(case.as_bytes()));
+        match result {
+            Ok(()) => println!("Saved to {}", path),
+            Err(error) => eprintln!("Could not save to {}: {}", path, error),
+        }
+    }
+    std::process::exit(1);
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() < 2 {
-        for i in 1..15 {
-            execute_day(i);
+    let time = args.iter().any(|x| x == "--time");
+    if args.len() < 2 || args[1].starts_with("--") {
+        single_day_input(&args, &[]);
+        let format = report_format(&args);
+        let mut total = std::time::Duration::ZERO;
+        for solution in solutions() {
+            let report = execute_day(solution.as_ref(), None);
+            total += report.total_time();
+            print_day(&report, format, time);
+        }
+        if time && format == report::Format::Text {
+            println!("Total: {:.1?}", total);
         }
+    } else if args[1] == "bench" {
+        bench(&args[2..]);
+    } else if args[1] == "assemble" {
+        assemble(args.get(2).expect("Usage: assemble <source>"));
+    } else if args[1] == "run" {
+        run(&args[2..]);
+    } else if args[1] == "debug" {
+        debug(args.get(2).expect("Usage: debug <program>"));
+    } else if args[1] == "ascii" {
+        ascii(args.get(2).expect("Usage: ascii <program>"));
+    } else if args[1] == "symbolic" {
+        symbolic(&args[2..]);
+    } else if args[1] == "fuzz" {
+        fuzz(&args[2..]);
+    } else if args[1] == "verify" {
+        verify(&args[2..]);
+    } else if args[1] == "diff" {
+        diff(&args[2..]);
+    } else if args[1] == "convert" {
+        convert(&args[2..]);
+    } else if args[1] == "coverage" {
+        coverage(&args[2..]);
+    } else if args[1] == "network" {
+        network(&args[2..]);
+    } else if args[1] == "cfg" {
+        cfg(args.get(2).expect("Usage: cfg <program>"));
+    } else if args[1] == "disassemble" {
+        disassemble(args.get(2).expect("Usage: disassemble <program>"));
     } else {
-        let day_arg = &args[1];
-        let target_day = day_arg.parse::<usize>().unwrap();
-        execute_day(target_day);
+        let target_day = args[1].parse::<usize>().expect("Unknown command");
+        match solutions().into_iter().find(|x| x.day() == target_day) {
+            Some(solution) => {
+                let report = execute_day(solution.as_ref(), option_value(&args, "--input"));
+                print_day(&report, report_format(&args), time);
+            }
+            None => {
+                eprintln!("Day {} not implemented", target_day);
+                std::process::exit(1);
+            }
+        }
     }
 }
@@ -0,0 +1,164 @@
+use crate::solution::Answer;
+use std::time::Duration;
+
+/// How the runner prints each day.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    Text,
+    /// One JSON object per line and day, for scripts to read.
+    Json,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+/// The answers a day gave and how long each step took.
+#[derive(Debug, Clone)]
+pub struct DayReport {
+    pub day: usize,
+    pub title: String,
+    /// Reading the input file.
+    pub read_time: Duration,
+    /// Turning the input into what both parts work on.
+    pub parse_time: Duration,
+    pub parts: Vec<(Answer, Duration)>,
+}
+
+/// Quotes a string for JSON.
+fn json_string(text: &str) -> String {
+    let mut output = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+impl DayReport {
+    pub fn total_time(&self) -> Duration {
+        self.read_time + self.parse_time + self.parts.iter().map(|x| x.1).sum::<Duration>()
+    }
+
+    /// The answers as the runner has always printed them, optionally followed
+    /// by the times.
+    pub fn to_text(&self, time: bool) -> String {
+        let mut output = format!("Day {}: {}\n", self.day, self.title);
+        for (index, (answer, _)) in self.parts.iter().enumerate() {
+            output.push_str(&format!(
+                "Part {}:{}{}\n",
+                index + 1,
+                answer.separator(),
+                answer
+            ));
+        }
+        if time {
+            let parts: Vec<String> = self
+                .parts
+                .iter()
+                .enumerate()
+                .map(|(index, (_, duration))| format!("part {} {:.1?}", index + 1, duration))
+                .collect();
+            output.push_str(&format!(
+                "Time: read input {:.1?}, parse {:.1?}, {}\n",
+                self.read_time,
+                self.parse_time,
+                parts.join(", ")
+            ));
+        }
+        output
+    }
+
+    /// A single line of JSON. Numeric answers go in `answer` and images in
+    /// `image`, with the other one null.
+    pub fn to_json(&self) -> String {
+        let parts: Vec<String> = self
+            .parts
+            .iter()
+            .enumerate()
+            .map(|(index, (answer, duration))| {
+                let (answer, image) = match answer {
+                    Answer::Number(value) => (value.to_string(), "null".to_string()),
+                    Answer::Image(image) => ("null".to_string(), json_string(image)),
+                };
+                format!(
+                    "{{\"part\":{},\"answer\":{},\"image\":{},\"time_ms\":{}}}",
+                    index + 1,
+                    answer,
+                    image,
+                    milliseconds(*duration)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"day\":{},\"title\":{},\"parts\":[{}],\"read_time_ms\":{},\"parse_time_ms\":{},\"time_ms\":{}}}",
+            self.day,
+            json_string(&self.title),
+            parts.join(","),
+            milliseconds(self.read_time),
+            milliseconds(self.parse_time),
+            milliseconds(self.total_time())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report::json_string;
+    use crate::report::DayReport;
+    use crate::solution::Answer;
+    use std::time::Duration;
+
+    fn report() -> DayReport {
+        DayReport {
+            day: 8,
+            title: "Space \"Image\" Format".to_string(),
+            read_time: Duration::from_micros(250),
+            parse_time: Duration::from_micros(500),
+            parts: vec![
+                (Answer::Number(1452), Duration::from_millis(2)),
+                (
+                    Answer::Image("# #\n # \n".to_string()),
+                    Duration::from_millis(3),
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(json_string("a\\b\t"), "\"a\\\\b\\u0009\"");
+        assert_eq!(
+            report().to_json(),
+            "{\"day\":8,\"title\":\"Space \\\"Image\\\" Format\",\"parts\":[\
+             {\"part\":1,\"answer\":1452,\"image\":null,\"time_ms\":2.000},\
+             {\"part\":2,\"answer\":null,\"image\":\"# #\\n # \\n\",\"time_ms\":3.000}],\
+             \"read_time_ms\":0.250,\"parse_time_ms\":0.500,\"time_ms\":5.750}"
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(
+            report().to_text(true),
+            "Day 8: Space \"Image\" Format\nPart 1: 1452\nPart 2:\n# #\n # \n\
+             Time: read input 250.0µs, parse 500.0µs, part 1 2.0ms, part 2 3.0ms\n"
+        );
+    }
+}
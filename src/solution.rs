@@ -0,0 +1,158 @@
+use std::any::Any;
+use std::fmt;
+
+/// The answer to one part of a puzzle.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Answer {
+    Number(i64),
+    /// Letters drawn with `#`, one line per row.
+    Image(String),
+}
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Answer {
+        Answer::Number(value)
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(value: i32) -> Answer {
+        Answer::Number(value as i64)
+    }
+}
+
+/// Answers are signed, so one too large for an `i64` is a bug in the day.
+impl From<u64> for Answer {
+    fn from(value: u64) -> Answer {
+        Answer::Number(i64::try_from(value).expect("Answer does not fit in an i64"))
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Answer {
+        Answer::Number(i64::try_from(value).expect("Answer does not fit in an i64"))
+    }
+}
+
+impl Answer {
+    /// Printed after "Part N:", so that images start on a line of their own.
+    pub fn separator(&self) -> &'static str {
+        match self {
+            Answer::Number(_) => " ",
+            Answer::Image(_) => "\n",
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Number(value) => write!(f, "{}", value),
+            Answer::Image(image) => write!(f, "{}", image.trim_end_matches('\n')),
+        }
+    }
+}
+
+/// One day's puzzle. The input is parsed once and both parts get the parsed
+/// form. Neither part may print.
+pub trait Solution {
+    type Input;
+
+    fn day(&self) -> usize;
+
+    fn title(&self) -> &'static str;
+
+    fn parse(&self, input: &str) -> Self::Input;
+
+    fn part1(&self, input: &Self::Input) -> Answer;
+
+    fn part2(&self, input: &Self::Input) -> Answer;
+
+    /// Where the puzzle input is read from unless another file is given.
+    fn input_path(&self) -> String {
+        format!("data/day{:02}.txt", self.day())
+    }
+}
+
+/// An input parsed by some day's `Solution`.
+pub type Parsed = Box<dyn Any>;
+
+/// A `Solution` with its input type hidden, so that every day can be kept in
+/// one list.
+pub trait AnySolution {
+    fn day(&self) -> usize;
+
+    fn title(&self) -> &'static str;
+
+    fn input_path(&self) -> String;
+
+    fn parse(&self, input: &str) -> Parsed;
+
+    /// Solves `part` of the puzzle. Panics if `input` was parsed by another day.
+    fn solve(&self, part: usize, input: &Parsed) -> Answer;
+}
+
+impl<S: Solution> AnySolution for S
+where
+    S::Input: 'static,
+{
+    fn day(&self) -> usize {
+        Solution::day(self)
+    }
+
+    fn title(&self) -> &'static str {
+        Solution::title(self)
+    }
+
+    fn input_path(&self) -> String {
+        Solution::input_path(self)
+    }
+
+    fn parse(&self, input: &str) -> Parsed {
+        Box::new(Solution::parse(self, input))
+    }
+
+    fn solve(&self, part: usize, input: &Parsed) -> Answer {
+        let input = input
+            .downcast_ref::<S::Input>()
+            .expect("Input parsed by another day");
+        match part {
+            1 => self.part1(input),
+            _ => self.part2(input),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solution::Answer;
+    use crate::solutions;
+
+    #[test]
+    fn test_registry_covers_every_day() {
+        let days: Vec<usize> = solutions().iter().map(|x| x.day()).collect();
+        let expected: Vec<usize> = (1..=days.len()).collect();
+        assert_eq!(days, expected);
+        assert!(solutions().iter().all(|x| !x.title().is_empty()));
+    }
+
+    #[test]
+    fn test_parse_once_for_both_parts() {
+        let day01 = &solutions()[0];
+        let parsed = day01.parse("14\n1969\n");
+        assert_eq!(day01.solve(1, &parsed), Answer::Number(2 + 654));
+        assert_eq!(day01.solve(2, &parsed), Answer::Number(2 + 966));
+    }
+
+    #[test]
+    fn test_answer_display() {
+        assert_eq!(Answer::from(42_usize).to_string(), "42");
+        assert_eq!(Answer::Image("#.\n.#\n".to_string()).to_string(), "#.\n.#");
+    }
+
+    #[test]
+    #[should_panic(expected = "Answer does not fit in an i64")]
+    fn test_answer_too_large() {
+        let _ = Answer::from(u64::MAX);
+    }
+}
@@ -0,0 +1,56 @@
+use std::time::Duration;
+use std::time::Instant;
+
+/// Runs `f`, returning its result and how long it took.
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// Summary of repeated measurements of the same thing.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+}
+
+impl Stats {
+    /// Uses the nearest-rank method, so every figure is one of the samples.
+    pub fn from_samples(samples: &[Duration]) -> Option<Stats> {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let rank = |percentile: usize| {
+            let index = (percentile * sorted.len()).div_ceil(100);
+            sorted[index.max(1) - 1]
+        };
+        Some(Stats {
+            min: *sorted.first()?,
+            median: rank(50),
+            p95: rank(95),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::timing::Stats;
+    use std::time::Duration;
+
+    #[test]
+    fn test_stats() {
+        let samples: Vec<Duration> = (1..=20).rev().map(Duration::from_millis).collect();
+        assert_eq!(
+            Stats::from_samples(&samples),
+            Some(Stats {
+                min: Duration::from_millis(1),
+                median: Duration::from_millis(10),
+                p95: Duration::from_millis(19),
+            })
+        );
+        let single = Stats::from_samples(&[Duration::from_millis(3)]).unwrap();
+        assert_eq!(single.p95, Duration::from_millis(3));
+        assert_eq!(Stats::from_samples(&[]), None);
+    }
+}
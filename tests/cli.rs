@@ -0,0 +1,133 @@
+use std::path::Path;
+use std::process::Command;
+
+fn aoc2019(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc2019"))
+        .args(args)
+        .current_dir(std::env::temp_dir())
+        .output()
+        .expect("Should have been able to run the binary")
+}
+
+fn data(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join(name)
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+fn test_day_with_absolute_input_from_another_directory() {
+    for (day, expected) in [("2", "Part 1: 3850704\n"), ("13", "Part 2: 15973\n")] {
+        let input = data(&format!("day{:0>2}.txt", day));
+        let output = aoc2019(&[day, "--input", &input]);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(stdout.contains(expected), "{}", stdout);
+    }
+}
+
+#[test]
+fn test_unreadable_program() {
+    for command in ["run", "convert"] {
+        let output = aoc2019(&[command, "no-such-program.txt", "out.icb"]);
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.starts_with("Could not read no-such-program.txt: "),
+            "{}",
+            stderr
+        );
+    }
+}
+
+#[test]
+fn test_bench_days_around_options() {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc2019"))
+        .args(["bench", "1", "--runs", "1", "--time", "4"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("Should have been able to run the binary");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let days: Vec<&str> = stdout
+        .lines()
+        .skip(2)
+        .filter_map(|x| x.split_whitespace().next())
+        .collect();
+    assert_eq!(days, ["1", "1", "4", "4"], "{}", stdout);
+}
+
+#[test]
+fn test_convert_with_format() {
+    let output_path = std::env::temp_dir().join("aoc2019-convert-format.bin");
+    let output_path = output_path.to_string_lossy().to_string();
+    let output = aoc2019(&[
+        "convert",
+        &data("day09.txt"),
+        &output_path,
+        "--format",
+        "binary",
+    ]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let written = std::fs::read(&output_path).unwrap();
+    std::fs::remove_file(&output_path).unwrap();
+    assert!(written.starts_with(b"ICIM"));
+}
+
+#[test]
+fn test_input_needs_a_single_day() {
+    let input = data("day01.txt");
+    for args in [
+        vec!["--input", &input],
+        vec!["bench", "--input", &input],
+        vec!["verify", "1", "2", "--input", &input],
+    ] {
+        let output = aoc2019(&args);
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains("--input needs exactly one day"),
+            "{}",
+            stderr
+        );
+    }
+    let output = aoc2019(&["bench", "1", "--input", &input, "--runs", "1"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(stdout.lines().count(), 4, "{}", stdout);
+}
+
+#[test]
+fn test_coverage_of_faulting_program() {
+    let program_path = std::env::temp_dir().join("aoc2019-coverage-fault.txt");
+    std::fs::write(&program_path, "1101,2,3,5,1001,0,0,-1\n").unwrap();
+    let output = aoc2019(&["coverage", &program_path.to_string_lossy()]);
+    std::fs::remove_file(&program_path).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("Executed 1 of 2 instructions"),
+        "{}",
+        stdout
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("negative address -1"), "{}", stderr);
+}